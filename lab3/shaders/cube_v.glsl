@@ -10,11 +10,7 @@ layout(location = 1) uniform mat4 view_matrix;
 out vec4 f_color;
 
 
-vec4 world_to_clip_space(vec4 position) {
-    vec4 view_space_position = view_matrix * position;
-    vec4 clip_space_position = perspective_matrix * view_space_position;
-    return clip_space_position;
-}
+#include "transform.glsl"
 
 
 void main(void) {
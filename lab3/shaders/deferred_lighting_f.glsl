@@ -0,0 +1,48 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+// matches `Lighting::MAX_LIGHTS`.
+const int MAX_LIGHTS = 8;
+const int DIRECTIONAL = 0;
+const float AMBIENT = 0.1;
+
+struct Light {
+    int kind;
+    vec3 position;
+    vec3 color;
+    float intensity;
+};
+
+uniform Light lights[MAX_LIGHTS];
+uniform int light_count;
+
+layout(binding = 0) uniform sampler2D g_position;
+layout(binding = 1) uniform sampler2D g_normal;
+layout(binding = 2) uniform sampler2D g_albedo;
+
+in vec2 f_uv;
+out vec4 pixel_color;
+
+void main(void) {
+    vec4 albedo = texture(g_albedo, f_uv);
+    if (albedo.a == 0.0) {
+        discard;
+    }
+    vec3 normal = texture(g_normal, f_uv).xyz;
+    if (normal == vec3(0.0)) {
+        pixel_color = albedo;
+        return;
+    }
+    vec3 position = texture(g_position, f_uv).xyz;
+
+    vec3 color = AMBIENT * albedo.rgb;
+    for (int i = 0; i < light_count; i++) {
+        // directional lights store the direction light is travelling in.
+        bool directional = lights[i].kind == DIRECTIONAL;
+        vec3 to_light = directional ? -lights[i].position : lights[i].position - position;
+        float attenuation = directional ? 1.0 : 1.0 / (1.0 + dot(to_light, to_light));
+        float diffuse = max(dot(normal, normalize(to_light)), 0.0);
+        color += albedo.rgb * lights[i].color * lights[i].intensity * attenuation * diffuse;
+    }
+    pixel_color = vec4(color, 1.0);
+}
@@ -0,0 +1,4 @@
+// requires double_sided uniform to be declared by including shader, see `Painter::double_sided`.
+vec3 facing_normal(vec3 normal) {
+    return double_sided && !gl_FrontFacing ? -normal : normal;
+}
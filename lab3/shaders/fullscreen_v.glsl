@@ -0,0 +1,10 @@
+#version 450
+
+out vec2 f_uv;
+
+void main(void) {
+    // single triangle (-1, -1), (3, -1), (-1, 3) covers the whole ndc square, clipping removes the rest.
+    vec2 uv = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    f_uv = uv;
+    gl_Position = vec4(uv * 2.0 - 1.0, 1.0, 1.0);
+}
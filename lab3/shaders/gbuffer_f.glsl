@@ -0,0 +1,19 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+layout(location = 2) uniform vec3 albedo;
+
+in vec3 f_position;
+in vec3 f_normal;
+
+layout(location = 0) out vec4 g_position;
+layout(location = 1) out vec4 g_normal;
+layout(location = 2) out vec4 g_albedo;
+
+void main(void) {
+    g_position = vec4(f_position, 1.0);
+    // geometry without normal buffer feeds zero vectors, lighting pass leaves it unlit.
+    g_normal = vec4(length(f_normal) > 0.0 ? normalize(f_normal) : vec3(0.0), 0.0);
+    // alpha marks covered pixels, background keeps cleared zero.
+    g_albedo = vec4(albedo, 1.0);
+}
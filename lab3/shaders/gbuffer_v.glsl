@@ -0,0 +1,27 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+// per instance model matrices, see `BinderBuilder::add_instance_transforms`, read only when `instanced` is set.
+layout(location = 2) in mat4 instance_transform;
+
+layout(location = 0) uniform mat4 perspective_matrix;
+layout(location = 1) uniform mat4 view_matrix;
+layout(location = 3) uniform mat4 model_matrix;
+layout(location = 4) uniform bool instanced;
+
+out vec3 f_position;
+out vec3 f_normal;
+
+#include "transform.glsl"
+
+void main(void) {
+    mat4 model = instanced ? model_matrix * instance_transform : model_matrix;
+    vec4 world_position = model * vec4(position, 1.0);
+    // inverse transpose keeps normals perpendicular to surfaces under non uniform scale.
+    mat3 normal_matrix = transpose(inverse(mat3(model)));
+    f_position = world_position.xyz;
+    f_normal = normal_matrix * normal;
+    gl_Position = world_to_clip_space(world_position);
+}
@@ -6,8 +6,14 @@ layout(location = 0) in vec3 rotation;
 layout(location = 0) uniform mat4 perspective_matrix;
 layout(location = 1) uniform mat4 view_matrix;
 layout(location = 2) uniform float grid_size;
+layout(location = 3) uniform mat4 light_space_matrix;
+// read by labyrinth_f.glsl, declared so that shadow program sharing this shader accepts them.
+layout(location = 4) uniform vec3 light_direction;
+layout(location = 5) uniform bool double_sided;
 
 out vec4 f_color;
+out vec4 f_light_space_position;
+out vec3 f_normal;
 
 // const float grid_size = 10.0;
 
@@ -59,24 +65,24 @@ vec3 instance_offset() {
     return grid_center;
 }
 
-vec4 world_to_clip_space(vec4 position) {
-    vec4 view_space_position = view_matrix * position;
-    vec4 clip_space_position = perspective_matrix * view_space_position;
-    return clip_space_position;
-}
+#include "transform.glsl"
 
 void main(void) {
     float scale = 1 / float(grid_size);
     vec3 position = model[int(mod(gl_VertexID, 3))];
     vec4 rotated = vec4(position, 1.0);;
+    vec4 normal = vec4(0.0, 0.0, 1.0, 0.0);
     for (int i = 0; i < 3; i++) {
         mat4 matrix = rotation_matrix(rot_axis[i], rotation[i]);
         rotated *= matrix;
+        normal *= matrix;
     }
 
     vec3 v_index_pos = rotated.xyz * scale / 2 + instance_offset();
 
     f_color = vec4(instance_color(v_index_pos), 1.0);
+    f_normal = normal.xyz;
     vec4 world_position = vec4(v_index_pos, 1.0);
     gl_Position = world_to_clip_space(world_position);
+    f_light_space_position = light_space_matrix * world_position;
 }
\ No newline at end of file
@@ -0,0 +1,9 @@
+#version 450
+
+const vec3 OUTLINE_COLOR = vec3(1.0, 0.6, 0.0);
+
+out vec4 pixel_color;
+
+void main(void) {
+   pixel_color = vec4(OUTLINE_COLOR, 1.0);
+}
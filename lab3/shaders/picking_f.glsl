@@ -0,0 +1,9 @@
+#version 450
+
+uniform uint object_id;
+
+out uint pixel_id;
+
+void main(void) {
+   pixel_id = object_id;
+}
@@ -0,0 +1,15 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+in vec4 f_color;
+out vec4 pixel_color;
+
+void main(void) {
+    vec2 offset = gl_PointCoord * 2.0 - 1.0;
+    float radius = length(offset);
+    if (radius > 1.0) {
+        discard;
+    }
+    float alpha = 1.0 - smoothstep(1.0 - fwidth(radius) * 2.0, 1.0, radius);
+    pixel_color = vec4(f_color.rgb, f_color.a * alpha);
+}
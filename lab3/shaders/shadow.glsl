@@ -0,0 +1,9 @@
+// light_space_position is light_space_matrix * world space position, shadow_map is ShadowMap depth texture.
+// returns 1 for lit and 0 for shadowed fragments, positions outside of the light frustum are lit.
+float shadow_visibility(sampler2DShadow shadow_map, vec4 light_space_position) {
+    vec3 coordinates = light_space_position.xyz / light_space_position.w * 0.5 + 0.5;
+    if (coordinates.z > 1.0) {
+        return 1.0;
+    }
+    return texture(shadow_map, coordinates);
+}
@@ -0,0 +1,10 @@
+#version 450
+
+layout(binding = 0) uniform samplerCube skybox;
+
+in vec3 f_direction;
+out vec4 pixel_color;
+
+void main(void) {
+   pixel_color = texture(skybox, f_direction);
+}
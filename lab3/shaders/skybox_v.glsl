@@ -0,0 +1,16 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+layout(location = 0) in vec3 position;
+
+layout(location = 0) uniform mat4 perspective_matrix;
+layout(location = 1) uniform mat4 view_matrix;
+
+out vec3 f_direction;
+
+void main(void) {
+    f_direction = position;
+    vec4 clip_space_position = perspective_matrix * view_matrix * vec4(position, 1.0);
+    // force depth to the far plane so that skybox is drawn behind everything else.
+    gl_Position = clip_space_position.xyww;
+}
@@ -1,8 +1,24 @@
 #version 450
+#extension GL_ARB_explicit_uniform_location : require
 
-in vec4 f_color;
+// shadowed fragments keep this fraction of their color.
+const float SHADOW_AMBIENT = 0.4;
+const vec3 BALL_COLOR = vec3(1, 0.85, 0.82);
+
+layout(location = 2) uniform vec3 light_direction;
+layout(location = 5) uniform bool double_sided;
+layout(binding = 1) uniform sampler2DShadow shadow_map;
+
+in vec4 f_light_space_position;
+in vec3 f_normal;
 out vec4 pixel_color;
 
+#include "shadow.glsl"
+#include "double_sided.glsl"
+
 void main(void) {
-   pixel_color = f_color;
-}
\ No newline at end of file
+   vec3 normal = facing_normal(normalize(f_normal));
+   vec3 color = BALL_COLOR * max(dot(normal, normalize(light_direction)), 0.0);
+   float visibility = shadow_visibility(shadow_map, f_light_space_position);
+   pixel_color = vec4(color * mix(SHADOW_AMBIENT, 1.0, visibility), 1.0);
+}
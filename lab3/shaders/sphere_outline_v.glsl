@@ -0,0 +1,18 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+// uniform layout of sphere_v.glsl, sphere is scaled up so that only its rim passes the outline stencil test.
+layout(location = 0) in vec3 position;
+
+layout(location = 0) uniform mat4 perspective_matrix;
+layout(location = 1) uniform mat4 view_matrix;
+layout(location = 3) uniform vec3 player_position;
+
+const float MODEL_SCALE = 0.1;
+const float OUTLINE_SCALE = 1.15;
+
+#include "transform.glsl"
+
+void main(void) {
+    gl_Position = world_to_clip_space(vec4(position * MODEL_SCALE * OUTLINE_SCALE + player_position, 1.0));
+}
@@ -6,22 +6,21 @@ layout(location = 1) in vec3 normal;
 
 layout(location = 0) uniform mat4 perspective_matrix;
 layout(location = 1) uniform mat4 view_matrix;
+// read by sphere_f.glsl, declared so that shadow and picking programs sharing this shader accept it.
 layout(location = 2) uniform vec3 light_direction;
 layout(location = 3) uniform vec3 player_position;
+layout(location = 4) uniform mat4 light_space_matrix;
 
-out vec4 f_color;
+out vec4 f_light_space_position;
+out vec3 f_normal;
 
-const vec3 BALL_COLOR = vec3(1, 0.85, 0.82);
 const float MODEL_SCALE = 0.1;
 
-vec4 world_to_clip_space(vec4 position) {
-    vec4 view_space_position = view_matrix * position;
-    vec4 clip_space_position = perspective_matrix * view_space_position;
-    return clip_space_position;
-}
+#include "transform.glsl"
 
 void main(void) {
     vec4 world_space_position = vec4(position * MODEL_SCALE + player_position , 1.0);
     gl_Position = world_to_clip_space(world_space_position);
-    f_color = vec4(BALL_COLOR * dot(normal, normalize(light_direction)), 1.0);
+    f_light_space_position = light_space_matrix * world_space_position;
+    f_normal = normal;
 }
\ No newline at end of file
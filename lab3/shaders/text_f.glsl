@@ -0,0 +1,17 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+layout(binding = 0) uniform sampler2D font_atlas;
+layout(location = 1) uniform vec3 text_color;
+
+in vec2 f_uv;
+out vec4 pixel_color;
+
+void main(void) {
+    // glyph coverage is read from atlas alpha so that both white on transparent and colored atlases work.
+    float coverage = texture(font_atlas, f_uv).a;
+    if (coverage == 0.0) {
+        discard;
+    }
+    pixel_color = vec4(text_color, coverage);
+}
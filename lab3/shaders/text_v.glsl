@@ -0,0 +1,14 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+// xy - position in pixels, zw - atlas coordinates.
+layout(location = 0) in vec4 glyph_vertex;
+
+layout(location = 0) uniform mat4 projection_matrix;
+
+out vec2 f_uv;
+
+void main(void) {
+    gl_Position = projection_matrix * vec4(glyph_vertex.xy, 0.0, 1.0);
+    f_uv = glyph_vertex.zw;
+}
@@ -0,0 +1,6 @@
+// requires perspective_matrix and view_matrix uniforms to be declared by including shader.
+vec4 world_to_clip_space(vec4 position) {
+    vec4 view_space_position = view_matrix * position;
+    vec4 clip_space_position = perspective_matrix * view_space_position;
+    return clip_space_position;
+}
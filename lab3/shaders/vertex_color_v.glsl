@@ -0,0 +1,17 @@
+#version 450
+#extension GL_ARB_explicit_uniform_location : require
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 color;
+
+layout(location = 0) uniform mat4 perspective_matrix;
+layout(location = 1) uniform mat4 view_matrix;
+
+out vec4 f_color;
+
+#include "transform.glsl"
+
+void main(void) {
+    gl_Position = world_to_clip_space(vec4(position, 1.0));
+    f_color = vec4(color, 1.0);
+}
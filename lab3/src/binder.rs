@@ -1,5 +1,6 @@
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
 use std::path::Display;
 use crate::{vertex, program, drawing, index_buffer, uniform};
 
@@ -8,42 +9,226 @@ use uniform::TypedUniform;
 use std::ops::Deref;
 use program::Program;
 use index_buffer::{IndexBuffer, IndexingMode, IndexType};
-use crate::uniform::{UniformType, UniformTypeProvider};
+use crate::uniform::{UniformType, UniformTypeProvider, NamedUniform};
 
 
-// fixme: attribute / uniform layout provider - as of now layouts are specified in order.
+// fixme: uniform layout provider - as of now uniform layout is specified in order, attributes can use `AttributeLocation`.
 //      quick solution -> print the manifest of (current layout - glsl lifetime - name)?
 
+/// Vertex shader input fed by a vbo.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AttributeLocation {
+    /// Location following attributes of all preceding vbos.
+    Sequential,
+    /// Location of active attribute with given name, queried from the program.
+    Named(&'static str),
+    /// Location given by `layout(location = N)` in the shader.
+    Explicit(usize),
+}
+
+impl Default for AttributeLocation {
+    fn default() -> Self { AttributeLocation::Sequential }
+}
+
+/// Resolve `(location, location count)` of every vbo into its first attribute location, see `Binder::attribute_locations`.
+fn resolve_attribute_locations(
+    locations: &[(AttributeLocation, usize)],
+    named: impl Fn(&'static str) -> usize,
+    max_vertex_attribs: usize,
+) -> Vec<usize> {
+    let mut sequential = 0;
+    let mut taken: Vec<std::ops::Range<usize>> = Vec::with_capacity(locations.len());
+    locations.iter()
+        .map(|&(location, location_count)| {
+            let resolved = match location {
+                AttributeLocation::Sequential => sequential,
+                AttributeLocation::Named(name) => named(name),
+                AttributeLocation::Explicit(location) => location,
+            };
+            sequential += location_count;
+            let range = resolved..resolved + location_count;
+            assert!(
+                range.end <= max_vertex_attribs,
+                "attribute locations {:?} exceed GL_MAX_VERTEX_ATTRIBS ({})", range, max_vertex_attribs
+            );
+            if let Some(other) = taken.iter().find(|other| other.start < range.end && range.start < other.end) {
+                panic!("attribute locations {:?} overlap locations {:?} of another vbo", range, other);
+            }
+            taken.push(range);
+            resolved
+        })
+        .collect()
+}
+
+/// Binder's uniforms keyed by ident, location of each uniform is the order in which it was added.
+///
+/// Kept apart from `Binder` so that dirty tracking does not depend on gl objects.
+struct Uniforms {
+    values: HashMap<&'static str, (usize, Box<dyn TypedUniform>)>,
+    /// Uniforms changed since they were last bound to the program, see `sync`.
+    dirty: RefCell<HashSet<&'static str>>,
+}
+
+impl Uniforms {
+    fn new(uniforms_with_idents: impl Iterator<Item=NamedUniform>) -> Self {
+        let values: HashMap<_, _> = HashMap::from_iter(
+            uniforms_with_idents.enumerate().map(
+                |(index, (ident, uniform))| (ident, (index, uniform))
+            )
+        );
+        let dirty = RefCell::new(values.keys().copied().collect());
+        Self { values, dirty }
+    }
+
+    fn add(&mut self, ident: &'static str, uniform: Box<dyn TypedUniform>) {
+        // todo: index is never stored and depends on the order in uniforms - this is terrible fix it please xoxo
+        let new_index = self.values.len();
+        let entry = self.values.entry(ident);
+        match entry {
+            Entry::Occupied(_) => panic!("uniform with ident {} already exists", ident),
+            Entry::Vacant(location) => location.insert((new_index, uniform)),
+        };
+        self.dirty.get_mut().insert(ident);
+    }
+
+    fn update(&mut self, ident: &'static str, uniform: Box<dyn TypedUniform>) {
+        match self.values.get_mut(ident) {
+            Some((_, value)) => *value = uniform,
+            None => panic!("Uniform {} was not registered.", ident),
+        }
+        self.dirty.get_mut().insert(ident);
+    }
+
+    fn location(&self, ident: &str) -> Option<usize> {
+        self.values.get(ident).map(|(index, _)| *index)
+    }
+
+    fn bind_all(&self) {
+        for (index, uniform) in self.values.values() {
+            uniform.bind(*index as _);
+        }
+    }
+
+    fn sync(&self) {
+        let mut dirty = self.dirty.borrow_mut();
+        for ident in dirty.drain() {
+            let (index, uniform) = &self.values[ident];
+            uniform.bind(*index as _);
+        }
+    }
+
+    fn has_dirty(&self) -> bool {
+        !self.dirty.borrow().is_empty()
+    }
+
+    fn bind_time_varying(&self) {
+        for (index, uniform) in self.values.values() {
+            if uniform.is_time_varying() {
+                uniform.bind(*index as _);
+            }
+        }
+    }
+}
+
 pub struct Binder<I>
     where
         I: IndexBuffer
 {
     vao: vertex::ArrayObject,
     vbos: Vec<Box<dyn vertex::Buffer>>,
+    /// Vertex shader input fed by vbo with the same index.
+    attribute_locations: Vec<AttributeLocation>,
     ebo: IndexingMode<I>,
     program: Program,
-    uniforms: HashMap<&'static str, (usize, Box<dyn TypedUniform>)>,
+    uniforms: Uniforms,
+    procedural_vertex_count: Option<usize>,
 }
 
 impl<I> Binder<I> where I: IndexBuffer,
 {
+    pub fn builder() -> BinderBuilder<I> {
+        BinderBuilder::default()
+    }
+
     pub fn new(
         vbos: Vec<Box<dyn vertex::Buffer>>,
         ebo: IndexingMode<I>,
         program: Program,
-        uniforms_with_idents: impl Iterator<Item=(&'static str, Box<dyn TypedUniform>)>,
+        uniforms_with_idents: impl Iterator<Item=NamedUniform>,
+    ) -> Self {
+        assert!(
+            !vbos.is_empty() || ebo.is_some(),
+            "Binder requires at least one vertex buffer or an index buffer, use Binder::procedural for attribute-less geometry"
+        );
+        Self::validate_vertex_counts(&vbos);
+        Self::create(vbos, ebo, program, uniforms_with_idents, None)
+    }
+
+    /// Binder for geometry generated entirely in the vertex shader (e.g. from `gl_VertexID`).
+    pub fn procedural(
+        vertex_count: usize,
+        program: Program,
+        uniforms_with_idents: impl Iterator<Item=NamedUniform>,
+    ) -> Self {
+        Self::create(Vec::new(), None, program, uniforms_with_idents, Some(vertex_count))
+    }
+
+    fn create(
+        vbos: Vec<Box<dyn vertex::Buffer>>,
+        ebo: IndexingMode<I>,
+        program: Program,
+        uniforms_with_idents: impl Iterator<Item=NamedUniform>,
+        procedural_vertex_count: Option<usize>,
     ) -> Self {
         let vao = vertex::ArrayObject::create();
-        let uniforms = HashMap::from_iter(
-            uniforms_with_idents.enumerate().map(
-                |(index, (ident, uniform))| (ident, (index, uniform))
-            )
+        let uniforms = Uniforms::new(uniforms_with_idents);
+        let attribute_locations = vec![AttributeLocation::default(); vbos.len()];
+        Self { vao, vbos, attribute_locations, ebo, program, uniforms, procedural_vertex_count }
+    }
+
+    /// Feed vbo with given index to vertex shader input with given name instead of the next free location.
+    pub fn set_attribute_name(&mut self, vbo_index: usize, name: &'static str) {
+        self.set_attribute_location(vbo_index, AttributeLocation::Named(name));
+    }
+
+    /// Decouple location of vbo's attribute from its position among binder's vbos, takes effect on `upload`.
+    pub fn set_attribute_location(&mut self, vbo_index: usize, location: AttributeLocation) {
+        self.attribute_locations[vbo_index] = location;
+    }
+
+    /// Resolved attribute location of every vbo, in vbo order.
+    ///
+    /// Sequential locations count locations taken by all preceding vbos, including explicitly placed ones.
+    /// Panics if locations of two vbos overlap or exceed `GL_MAX_VERTEX_ATTRIBS`.
+    pub fn attribute_locations(&self) -> Vec<usize> {
+        let locations: Vec<_> = self.vbos.iter()
+            .zip(&self.attribute_locations)
+            .map(|(vbo, location)| (*location, vbo.attribute_type().location_count()))
+            .collect();
+        let mut max_vertex_attribs = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_VERTEX_ATTRIBS, &mut max_vertex_attribs); }
+        resolve_attribute_locations(
+            &locations,
+            |name| self.program.attribute_location(name)
+                .unwrap_or_else(|| panic!("program {} has no active attribute {}", self.program.id(), name)) as _,
+            max_vertex_attribs as _,
+        )
+    }
+
+    /// Per vertex buffers must agree on vertex count, instanced buffers are sized by instance count instead.
+    fn validate_vertex_counts(vbos: &[Box<dyn vertex::Buffer>]) {
+        let counts = vbos.iter()
+            .filter(|vbo| !vbo.is_instanced())
+            .map(|vbo| vbo.vertex_count())
+            .collect::<Vec<_>>();
+        assert!(
+            counts.windows(2).all(|pair| pair[0] == pair[1]),
+            "all per vertex buffers in a Binder must contain the same number of attributes, got {:?}", counts
         );
-        Self { vao, vbos, ebo, program, uniforms }
     }
 
     pub fn uniform_definitions(&self) -> Box<[String]> {
-        let mut definitions = self.uniforms.iter()
+        let mut definitions = self.uniforms.values.iter()
             .map(| (ident, (index, uniform))|
                 format!("layout(location = {}) {} {}", index, uniform.uniform_type(), ident)
             )
@@ -53,75 +238,138 @@ impl<I> Binder<I> where I: IndexBuffer,
         definitions
     }
 
+    /// Compare registered uniforms against uniforms active in the program.
+    ///
+    /// Returns description of every uniform whose location or type does not match.
+    /// Uniforms that are not active (e.g. optimized out by the compiler) are not reported.
+    pub fn validate_uniforms(&self) -> Result<(), Vec<String>> {
+        let active = self.program.active_uniforms();
+        let mut problems = Vec::new();
+        for (ident, (index, uniform)) in &self.uniforms.values {
+            let info = match active.iter().find(|info| info.name == *ident) {
+                Some(info) => info,
+                None => continue,
+            };
+            if info.location != *index as gl::types::GLint {
+                problems.push(format!(
+                    "uniform {} is registered at location {} but program declares it at {}", ident, index, info.location
+                ));
+            }
+            if let Some(gl_type) = uniform.uniform_type().gl_type() {
+                if gl_type != info.gl_type {
+                    problems.push(format!(
+                        "uniform {} is registered as {} but program declares type 0x{:X}", ident, uniform.uniform_type(), info.gl_type
+                    ));
+                }
+            }
+        }
+        if problems.is_empty() { Ok(()) } else { Err(problems) }
+    }
+
     // todo: scoped_binder controls if appropriate object is already bound if so it returns null binder of sort.
     //      uniform indexes or more generally should be provided and managed by and external object.
 
-    fn bind_uniforms(&self) {
-        for (index, uniform) in self.uniforms.values() {
-            uniform.bind(*index as _);
-        }
+    /// Upload all uniforms into currently used program, e.g. picking program sharing binder's uniform locations.
+    ///
+    /// Dirty flags are left intact as they track binder's own program.
+    pub(crate) fn bind_uniforms(&self) {
+        self.uniforms.bind_all();
+    }
+
+    /// Bind uniforms changed since previous sync, expects binder's program to be in use.
+    pub(crate) fn sync_uniforms(&self) {
+        self.uniforms.sync();
+    }
+
+    pub fn has_dirty_uniforms(&self) -> bool {
+        self.uniforms.has_dirty()
+    }
+
+    /// Rebind uniforms whose value changes over time, expects program to be in use.
+    pub(crate) fn bind_time_varying_uniforms(&self) {
+        self.uniforms.bind_time_varying();
     }
 
     pub fn add_uniform(&mut self, ident: &'static str, uniform: Box<dyn TypedUniform>) {
-        // todo: index is never stored and depends on the order in uniforms - this is terrible fix it please xoxo
-        let new_index = self.uniforms.len();
-        let entry = self.uniforms.entry(ident);
-        match entry {
-            Entry::Occupied(_) => panic!("uniform with ident {} already exists", ident),
-            Entry::Vacant(location) => location.insert((new_index, uniform)),
-        };
+        self.uniforms.add(ident, uniform);
     }
 
-    pub fn update_uniform(&mut self, ident: &'static str, uniform: Box<dyn TypedUniform>) {
-        let _program_binder = self.program_binder();
-        let mut entry = self.uniforms.entry(ident);
-        match entry {
-            Entry::Occupied(ref mut location) => {
-                let (index, _) = location.get();
-                uniform.bind(*index as _);
-                location.insert((*index, uniform));
-            }
-            Entry::Vacant(_) => panic!("Uniform {} was not registered.", ident),
-        }
+    pub fn uniform_location(&self, ident: &str) -> Option<usize> {
+        self.uniforms.location(ident)
+    }
 
+    /// Replace uniform's value, it is bound to the program on next `sync_uniforms` (i.e. next draw).
+    pub fn update_uniform(&mut self, ident: &'static str, uniform: Box<dyn TypedUniform>) {
+        self.uniforms.update(ident, uniform);
     }
 
     pub fn upload(&mut self) {
         let _program_scoped_binder = self.program.scoped_binder();
 
-        self.bind_uniforms();
-        println!("Uniform definitions for program: {}", self.program.id());
+        self.sync_uniforms();
+        log::debug!("Uniform definitions for program: {}", self.program.id());
         for uni_def in self.uniform_definitions().iter() {
-            println!("\t{}", uni_def);
+            log::debug!("\t{}", uni_def);
+        }
+        if let Err(problems) = self.validate_uniforms() {
+            for problem in problems {
+                log::warn!("program {}: {}", self.program.id(), problem);
+            }
         }
 
-        let _vao_binder = self.vao.scoped_binder();
-        for (index, vbo) in self.vbos.iter().enumerate() {
-            let _scoped_binder = vbo.as_ref().scoped_binder();
-            gl_assert_no_err!();
+        // every vbo gets binding point equal to its index, format and source are set independently.
+        let locations = self.attribute_locations();
+        for (vbo_index, (vbo, location)) in self.vbos.iter().zip(locations).enumerate() {
             vbo.as_ref().upload();
             gl_assert_no_err!();
-            self.vao.set_vertex_attrib_pointer(index as _, &vbo.as_ref().attribute_type());
-            gl_assert_no_err!();
+            let attribute_type = vbo.as_ref().attribute_type();
+            self.vao.set_vertex_buffer(vbo_index, vbo.id(), &attribute_type);
+            self.vao.set_attrib_format(location, &attribute_type, vbo_index);
+            if vbo.is_instanced() {
+                self.vao.set_binding_divisor(vbo_index, vbo.divisor());
+            }
         }
 
         if let Some(ref index_buffer) = self.ebo {
-            let _ebo_binder = index_buffer.scoped_binder();
             index_buffer.upload();
+            self.vao.set_element_buffer(index_buffer.id());
         }
     }
 
+    /// Replace vbo with given index by buffer of the same attribute type, attribute format is kept.
+    ///
+    /// Returned previous buffer must outlive draws that were already issued with it.
+    pub fn swap_vbo(&mut self, vbo_index: usize, vbo: Box<dyn vertex::Buffer>) -> Box<dyn vertex::Buffer> {
+        let previous = &self.vbos[vbo_index];
+        assert_eq!(
+            previous.attribute_type(), vbo.attribute_type(),
+            "swapped vbo must match attribute type of vbo {}", vbo_index
+        );
+        vbo.upload();
+        self.vao.set_vertex_buffer(vbo_index, vbo.id(), &vbo.attribute_type());
+        std::mem::replace(&mut self.vbos[vbo_index], vbo)
+    }
+
     pub fn vertex_count(&self) -> usize {
         // vertex count provider trait
         if let Some(ref index_buffer) = self.ebo {
             index_buffer.vertex_count()
-        } else if let Some(vbo) = self.vbos.first() {
+        } else if let Some(vbo) = self.vbos.iter().find(|vbo| !vbo.is_instanced()) {
             vbo.as_ref().vertex_count()
+        } else if let Some(vertex_count) = self.procedural_vertex_count {
+            vertex_count
         } else {
-            2
+            self.vbos.first().expect("Binder::new guarantees a vertex source").vertex_count()
         }
     }
 
+    /// Number of instances covered by instanced buffers, `None` if binder has none.
+    pub fn instance_count(&self) -> Option<usize> {
+        self.vbos.iter()
+            .find(|vbo| vbo.is_instanced())
+            .map(|vbo| vbo.vertex_count() * vbo.divisor())
+    }
+
     pub fn vao(&self) -> &vertex::array_object::ArrayObject {
         &self.vao
     }
@@ -135,17 +383,213 @@ impl<I> Binder<I> where I: IndexBuffer,
         self.ebo.as_ref().map(|index_buffer| index_buffer.index_type())
     }
 
-    pub fn program_binder(&self) -> program::ScopedBinder { self.program.scoped_binder() }
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn program_binder(&self) -> program::ScopedBinder<'_> { self.program.scoped_binder() }
 
-    pub fn draw_binder(&self) -> DrawScopedBinder {
+    pub fn draw_binder(&self) -> DrawScopedBinder<'_> {
         DrawScopedBinder::new(self.program_binder(), self.vao_binder())
     }
 }
 
-pub struct DrawScopedBinder(program::ScopedBinder, vertex::array_object::ScopedBinder);
+pub struct DrawScopedBinder<'a>(program::ScopedBinder<'a>, vertex::array_object::ScopedBinder);
 
-impl DrawScopedBinder {
-    pub fn new(program: program::ScopedBinder, vao: vertex::array_object::ScopedBinder) -> Self {
+impl<'a> DrawScopedBinder<'a> {
+    pub fn new(program: program::ScopedBinder<'a>, vao: vertex::array_object::ScopedBinder) -> Self {
         Self(program, vao)
     }
 }
+
+/// Step by step construction of a `Binder`.
+///
+/// Buffers are assigned consecutive attribute locations unless added with a name or location,
+/// uniforms are assigned consecutive uniform locations in the order they were added.
+pub struct BinderBuilder<I: IndexBuffer> {
+    vbos: Vec<Box<dyn vertex::Buffer>>,
+    attribute_locations: Vec<(usize, AttributeLocation)>,
+    ebo: IndexingMode<I>,
+    program: Option<Program>,
+    uniforms: Vec<NamedUniform>,
+    procedural_vertex_count: Option<usize>,
+}
+
+impl<I: IndexBuffer> Default for BinderBuilder<I> {
+    fn default() -> Self {
+        Self {
+            vbos: Vec::new(),
+            attribute_locations: Vec::new(),
+            ebo: None,
+            program: None,
+            uniforms: Vec::new(),
+            procedural_vertex_count: None,
+        }
+    }
+}
+
+impl<I: IndexBuffer> BinderBuilder<I> {
+    pub fn add_vbo(mut self, vbo: impl vertex::Buffer + 'static) -> Self {
+        self.vbos.push(Box::new(vbo));
+        self
+    }
+
+    /// Add buffer feeding vertex shader input with given name.
+    pub fn add_named_vbo(self, name: &'static str, vbo: impl vertex::Buffer + 'static) -> Self {
+        self.add_vbo_at(AttributeLocation::Named(name), vbo)
+    }
+
+    /// Add buffer feeding vertex shader input at given location, e.g. `AttributeLocation::Explicit(3)`.
+    pub fn add_vbo_at(mut self, location: AttributeLocation, vbo: impl vertex::Buffer + 'static) -> Self {
+        self.attribute_locations.push((self.vbos.len(), location));
+        self.vbos.push(Box::new(vbo));
+        self
+    }
+
+    /// Add buffer whose attribute advances once per `divisor` instances.
+    pub fn add_instanced_vbo<B: vertex::Buffer + 'static>(mut self, vbo: B, divisor: usize) -> Self {
+        self.vbos.push(Box::new(vertex::InstancedBuffer::new(vbo, divisor)));
+        self
+    }
+
+    /// Add per instance model matrices feeding `mat4` vertex shader input with given name.
+    pub fn add_instance_transforms(self, name: &'static str, transforms: &[nalgebra_glm::Mat4]) -> Self {
+        self.add_named_vbo(name, vertex::InstanceTransforms::new(transforms))
+    }
+
+    pub fn index_buffer(mut self, ebo: I) -> Self {
+        self.ebo = Some(ebo);
+        self
+    }
+
+    pub fn program(mut self, program: Program) -> Self {
+        self.program = Some(program);
+        self
+    }
+
+    pub fn add_uniform(mut self, ident: &'static str, uniform: Box<dyn TypedUniform>) -> Self {
+        self.uniforms.push((ident, uniform));
+        self
+    }
+
+    pub fn add_uniforms(mut self, uniforms: impl Iterator<Item=NamedUniform>) -> Self {
+        self.uniforms.extend(uniforms);
+        self
+    }
+
+    /// Vertices generated in the vertex shader per instance, for binders with only instanced buffers.
+    pub fn procedural_vertices(mut self, vertex_count: usize) -> Self {
+        self.procedural_vertex_count = Some(vertex_count);
+        self
+    }
+
+    pub fn build(self) -> Binder<I> {
+        let program = self.program.expect("BinderBuilder requires a program");
+        let mut binder = Binder::new(self.vbos, self.ebo, program, self.uniforms.into_iter());
+        for (vbo_index, location) in self.attribute_locations {
+            binder.set_attribute_location(vbo_index, location);
+        }
+        binder.procedural_vertex_count = self.procedural_vertex_count;
+        binder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uniform::Uniform;
+
+    thread_local! {
+        static BOUND: RefCell<Vec<gl::types::GLint>> = RefCell::new(Vec::new());
+    }
+
+    /// Uniform recording locations it was bound at instead of calling gl.
+    #[derive(Debug)]
+    struct Recorded;
+
+    impl Uniform for Recorded {
+        fn bind(&self, location: gl::types::GLint) {
+            BOUND.with(|bound| bound.borrow_mut().push(location));
+        }
+    }
+
+    impl UniformTypeProvider for Recorded {
+        fn uniform_type(&self) -> UniformType { UniformType::Float }
+    }
+
+    fn recorded() -> Box<dyn TypedUniform> {
+        Box::new(Recorded)
+    }
+
+    fn take_bound() -> Vec<gl::types::GLint> {
+        BOUND.with(|bound| {
+            let mut bound = bound.take();
+            bound.sort_unstable();
+            bound
+        })
+    }
+
+    #[test]
+    fn sync_binds_only_dirty_uniforms() {
+        let mut uniforms = Uniforms::new([("a", recorded()), ("b", recorded())].into_iter());
+        assert!(uniforms.has_dirty());
+        uniforms.sync();
+        assert!(!uniforms.has_dirty());
+        assert_eq!(take_bound(), [0, 1]);
+
+        uniforms.update("b", recorded());
+        assert!(uniforms.has_dirty());
+        uniforms.sync();
+        assert!(!uniforms.has_dirty());
+        assert_eq!(take_bound(), [1]);
+
+        uniforms.sync();
+        assert!(take_bound().is_empty());
+    }
+
+    #[test]
+    fn added_uniform_is_dirty_at_next_location() {
+        let mut uniforms = Uniforms::new([("a", recorded())].into_iter());
+        uniforms.sync();
+        take_bound();
+        uniforms.add("b", recorded());
+        assert_eq!(uniforms.location("b"), Some(1));
+        assert!(uniforms.has_dirty());
+        uniforms.sync();
+        assert_eq!(take_bound(), [1]);
+    }
+
+    #[test]
+    fn binding_all_uniforms_keeps_dirty_set() {
+        let uniforms = Uniforms::new([("a", recorded())].into_iter());
+        uniforms.bind_all();
+        assert!(uniforms.has_dirty());
+        assert_eq!(take_bound(), [0]);
+    }
+
+    #[test]
+    fn sequential_and_explicit_locations_resolve_in_vbo_order() {
+        let locations = [(AttributeLocation::Sequential, 1), (AttributeLocation::Explicit(3), 1)];
+        assert_eq!(resolve_attribute_locations(&locations, |_| unreachable!(), 16), vec![0, 3]);
+    }
+
+    #[test]
+    fn sequential_location_follows_locations_of_matrix_vbo() {
+        let locations = [(AttributeLocation::Sequential, 4), (AttributeLocation::Sequential, 1)];
+        assert_eq!(resolve_attribute_locations(&locations, |_| unreachable!(), 16), vec![0, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "overlap")]
+    fn explicit_location_inside_matrix_vbo_panics() {
+        let locations = [(AttributeLocation::Sequential, 4), (AttributeLocation::Explicit(3), 1)];
+        resolve_attribute_locations(&locations, |_| unreachable!(), 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "GL_MAX_VERTEX_ATTRIBS")]
+    fn location_past_max_vertex_attribs_panics() {
+        let locations = [(AttributeLocation::Explicit(14), 4)];
+        resolve_attribute_locations(&locations, |_| unreachable!(), 16);
+    }
+}
@@ -2,6 +2,11 @@ use core::borrow::Borrow;
 use nalgebra_glm as glm;
 use std::prelude::rust_2021::*;
 use nalgebra_glm::{Mat4, Vec3};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use glutin::dpi::PhysicalPosition;
+use crate::viewport::Viewport;
 use crate::{Direction, Directions, gl_assert_no_err};
 
 
@@ -75,6 +80,22 @@ impl FreeRoamingCamera {
     pub fn set_position(&mut self, new: Vec3) {
         self.camera.view.position = new;
     }
+
+    pub fn projection(&self) -> &Projection {
+        self.camera.projection()
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    pub fn projection_mut(&mut self) -> &mut Projection {
+        self.camera.projection_mut()
+    }
 }
 
 impl From<Camera> for FreeRoamingCamera {
@@ -115,7 +136,7 @@ impl PerspectiveMatrixProvider for FreeRoamingCamera {
 
 
 pub struct HeroShotCamera {
-    perspective: CameraPerspectiveState,
+    perspective: Projection,
     position: Vec3,
     rotation_point: Vec3,
     radius: f32,
@@ -126,7 +147,7 @@ impl HeroShotCamera {
     const LOWER_ZOOM_LIMIT: f32 = 0.1;
     const UPPER_ZOOM_LIMIT: f32 = 20.0;
 
-    pub fn new(perspective: CameraPerspectiveState, looking_direction: Vec3, rotation_point: Vec3, radius: f32) -> Self {
+    pub fn new(perspective: Projection, looking_direction: Vec3, rotation_point: Vec3, radius: f32) -> Self {
         Self {
             perspective,
             position: rotation_point - looking_direction * radius,
@@ -134,6 +155,14 @@ impl HeroShotCamera {
             radius
         }
     }
+
+    pub fn projection(&self) -> &Projection {
+        &self.perspective
+    }
+
+    pub fn projection_mut(&mut self) -> &mut Projection {
+        &mut self.perspective
+    }
 }
 
 impl Rotatable for HeroShotCamera {
@@ -141,8 +170,8 @@ impl Rotatable for HeroShotCamera {
         let mut transition = self.position - self.rotation_point;
         let coord_sys = RightHandCoordSys::new(-transition);
         let right = coord_sys.direction(&Direction::Right);
-        transition = glm::rotate_y_vec3(&transition, y_rad * Camera::SENSITIVITY);
-        transition = glm::rotate_vec3(&transition, x_rad * Camera::SENSITIVITY, &right);
+        transition = glm::rotate_y_vec3(&transition, y_rad);
+        transition = glm::rotate_vec3(&transition, x_rad, &right);
         self.position = self.rotation_point + transition;
     }
 }
@@ -173,15 +202,15 @@ impl ViewMatrixProvider for HeroShotCamera {
 
 
 // general camera
-#[derive(Debug, Clone)]
-pub struct CameraPerspectiveState {
-    aspect_ratio: f32,
-    fovy: f32,
-    z_near: f32,
-    z_far: f32
+/// Projection from view space to clip space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    /// `fovy` is vertical field of view in radians.
+    Perspective { aspect_ratio: f32, fovy: f32, z_near: f32, z_far: f32 },
+    Orthographic { left: f32, right: f32, bottom: f32, top: f32, z_near: f32, z_far: f32 },
 }
 
-impl Default for CameraPerspectiveState {
+impl Default for Projection {
     fn default() -> Self {
         let mut viewport = [0; 4];
         gl_assert_no_err!();
@@ -189,7 +218,7 @@ impl Default for CameraPerspectiveState {
         gl_assert_no_err!();
         let [.., width, height] = viewport;
 
-        Self::new(
+        Self::perspective(
             width as f32 / height as f32,
             Camera::DEFAULT_FOVY.to_radians(),
             Camera::DEFAULT_Z_NEAR,
@@ -198,21 +227,140 @@ impl Default for CameraPerspectiveState {
     }
 }
 
-impl CameraPerspectiveState {
-    pub fn new(aspect_ratio: f32, fovy: f32, z_near: f32, z_far: f32) -> Self {
-        Self { aspect_ratio, fovy, z_near, z_far }
+impl Projection {
+    pub const MIN_FOVY: f32 = 10.0;
+    pub const MAX_FOVY: f32 = 120.0;
+
+    pub fn perspective(aspect_ratio: f32, fovy: f32, z_near: f32, z_far: f32) -> Self {
+        Self::Perspective { aspect_ratio, fovy, z_near, z_far }
+    }
+
+    /// Orthographic projection of given box, depth is mapped to opengl's `[-1, 1]` ndc range.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, z_near: f32, z_far: f32) -> Self {
+        Self::Orthographic { left, right, bottom, top, z_near, z_far }
+    }
+
+    /// Orthographic projection in window pixels with origin in top-left corner, meant for hud and 2D overlays.
+    pub fn screen_space(width: f32, height: f32) -> Self {
+        Self::orthographic(0.0, width, height, 0.0, -1.0, 1.0)
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        match *self {
+            Self::Perspective { aspect_ratio, fovy, z_near, z_far } => {
+                glm::perspective(aspect_ratio, fovy, z_near, z_far)
+            },
+            Self::Orthographic { left, right, bottom, top, z_near, z_far } => {
+                glm::ortho(left, right, bottom, top, z_near, z_far)
+            },
+        }
+    }
+
+    /// Vertical field of view in radians, `None` for orthographic projection.
+    pub fn fovy(&self) -> Option<f32> {
+        match *self {
+            Self::Perspective { fovy, .. } => Some(fovy),
+            Self::Orthographic { .. } => None,
+        }
+    }
+
+    /// Change field of view of perspective projection, orthographic projection is left unchanged.
+    pub fn set_fovy(&mut self, new: f32) {
+        if let Self::Perspective { ref mut fovy, .. } = self {
+            *fovy = new;
+        }
+    }
+
+    /// Narrow (positive `degrees`) or widen field of view, clamped to `[MIN_FOVY, MAX_FOVY]` degrees.
+    pub fn zoom(&mut self, degrees: f32) {
+        if let Some(fovy) = self.fovy() {
+            let fovy = (fovy.to_degrees() - degrees).clamp(Self::MIN_FOVY, Self::MAX_FOVY);
+            self.set_fovy(fovy.to_radians());
+        }
+    }
+
+    /// Change aspect ratio of perspective projection, orthographic projection is left unchanged.
+    pub fn set_aspect_ratio(&mut self, new: f32) {
+        if let Self::Perspective { ref mut aspect_ratio, .. } = self {
+            *aspect_ratio = new;
+        }
+    }
+
+    pub fn clip_planes(&self) -> (f32, f32) {
+        match *self {
+            Self::Perspective { z_near, z_far, .. } | Self::Orthographic { z_near, z_far, .. } => (z_near, z_far),
+        }
+    }
+
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        match self {
+            Self::Perspective { z_near, z_far, .. } | Self::Orthographic { z_near, z_far, .. } => {
+                *z_near = near;
+                *z_far = far;
+            }
+        }
     }
 }
 
-impl PerspectiveMatrixProvider for CameraPerspectiveState {
+impl PerspectiveMatrixProvider for Projection {
     fn perspective_matrix(&self) -> Mat4 {
-        glm::perspective(self.aspect_ratio, self.fovy, self.z_near, self.z_far)
+        self.matrix()
+    }
+}
+
+/// Camera orientation stored as unit quaternion rotating camera's local axes into world space.
+///
+/// Unlike accumulating yaw and pitch on a direction vector this has no degenerate configuration when
+/// looking straight up or down, quaternion is renormalized after every rotation to counter float drift.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Orientation {
+    rotation: glm::Quat,
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self { rotation: glm::quat_identity() }
+    }
+}
+
+impl Orientation {
+    pub fn from_quat(rotation: glm::Quat) -> Self {
+        Self { rotation: glm::quat_normalize(&rotation) }
+    }
+
+    /// Orientation looking in given direction with no roll.
+    pub fn looking_at(direction: &Vec3) -> Self {
+        let direction = direction.normalize();
+        let yaw = f32::atan2(-direction.x, -direction.z);
+        let pitch = direction.y.clamp(-1.0, 1.0).asin();
+        Self::from_quat(glm::quat_angle_axis(yaw, &Directions::UP) * glm::quat_angle_axis(pitch, &Directions::RIGHT))
+    }
+
+    pub fn quat(&self) -> &glm::Quat {
+        &self.rotation
+    }
+
+    /// Rotate about world space axis.
+    pub fn rotate(&mut self, axis: &Vec3, angle: f32) {
+        self.rotation = glm::quat_normalize(&(glm::quat_angle_axis(angle, &axis.normalize()) * self.rotation));
+    }
+
+    pub fn direction(&self, direction: &Direction) -> Vec3 {
+        glm::quat_rotate_vec3(&self.rotation, &Directions::world().get(direction))
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.direction(&Direction::Front)
+    }
+
+    pub fn view_matrix(&self, position: &Vec3) -> Mat4 {
+        glm::quat_to_mat4(&glm::quat_conjugate(&self.rotation)) * glm::translation(&-position)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct CameraViewState {
-    pub looking_direction: Vec3,
+    pub orientation: Orientation,
     pub position: Vec3,
 }
 
@@ -220,26 +368,83 @@ impl Default for CameraViewState {
     fn default() -> Self {
         let looking_direction = Directions::FRONT;
         let position = glm::vec3(0f32, 0f32, 1f32);
-        Self { looking_direction, position }
+        Self::new(looking_direction, position)
     }
 }
 
 impl CameraViewState {
     pub fn new(looking_direction: Vec3, position: Vec3) -> Self {
-        Self { looking_direction, position }
+        Self { orientation: Orientation::looking_at(&looking_direction), position }
+    }
+
+    pub fn looking_direction(&self) -> Vec3 {
+        self.orientation.forward()
     }
 }
 
 impl ViewMatrixProvider for CameraViewState {
     fn view_matrix(&self) -> Mat4 {
-        let looking_point = self.position + self.looking_direction;
-        glm::look_at(&self.position, &looking_point, &Directions::UP)
+        self.orientation.view_matrix(&self.position)
+    }
+}
+
+/// Unproject cursor through inverse view-projection into world space ray `(origin, direction)`.
+///
+/// `cursor` is in window coordinates with top-left origin as reported by `WindowEvent::CursorMoved`,
+/// `viewport` must reach the top edge of the window. Origin lies on the near plane, direction is normalized.
+pub fn screen_to_ray(view: &Mat4, projection: &Mat4, cursor: PhysicalPosition<f64>, viewport: &Viewport) -> (Vec3, Vec3) {
+    let window_top = (viewport.y + viewport.height) as f64;
+    let x = 2.0 * (cursor.x - viewport.x as f64) / viewport.width as f64 - 1.0;
+    let y = 2.0 * (window_top - cursor.y - viewport.y as f64) / viewport.height as f64 - 1.0;
+
+    let inverse = glm::inverse(&(projection * view));
+    let unproject = |depth: f32| {
+        let world = inverse * glm::vec4(x as f32, y as f32, depth, 1.0);
+        world.xyz() / world.w
+    };
+    let near = unproject(-1.0);
+    let far = unproject(1.0);
+    (near, (far - near).normalize())
+}
+
+/// Persistable camera state, stored as plain arrays so that file format does not depend on glm.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraPose {
+    pub position: [f32; 3],
+    /// Orientation quaternion as `[x, y, z, w]`.
+    pub orientation: [f32; 4],
+    /// Vertical field of view in radians, absent for orthographic projection.
+    pub fovy: Option<f32>,
+}
+
+#[derive(Debug)]
+pub enum CameraPoseError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+impl Display for CameraPoseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CameraPoseError::Io(error) => write!(f, "failed to access camera pose file: {}", error),
+            CameraPoseError::Serialize(error) => write!(f, "failed to serialize camera pose: {}", error),
+            CameraPoseError::Deserialize(error) => write!(f, "invalid camera pose file: {}", error),
+        }
     }
 }
 
+impl std::error::Error for CameraPoseError { }
+
+impl From<std::io::Error> for CameraPoseError {
+    fn from(error: std::io::Error) -> Self { CameraPoseError::Io(error) }
+}
+
 pub struct Camera {
     view: CameraViewState,
-    perspective: CameraPerspectiveState,
+    perspective: Projection,
+    /// Distance travelled by single `move`.
+    speed: f32,
 }
 
 impl Camera {
@@ -247,23 +452,29 @@ impl Camera {
     const DEFAULT_Z_NEAR: f32 = 0.1;
     const DEFAULT_Z_FAR: f32 = 150.0;
 
-    const SENSITIVITY: f32 = 0.5;
     const SPEED: f32 = 0.05;
 
     pub fn angle() -> f32 {
         f32::to_radians(10f32)
     }
 
+    /// Pitch by `x_rot` about camera's right axis and yaw by `y_rot` about world up.
     pub fn rotate(&mut self, x_rot: f32, y_rot: f32) {
-        self.view.looking_direction = glm::rotate_y_vec3(&self.view.looking_direction, y_rot * Self::SENSITIVITY);
-        let coord_sys = RightHandCoordSys::new(self.view.looking_direction);
-        let right = coord_sys.direction(&Direction::Right);
-        self.view.looking_direction = glm::rotate_vec3(&self.view.looking_direction, x_rot * Self::SENSITIVITY, &right);
+        self.view.orientation.rotate(&Directions::UP, y_rot);
+        let right = self.view.orientation.direction(&Direction::Right);
+        self.view.orientation.rotate(&right, x_rot);
     }
 
     pub fn r#move(&mut self, direction: &Direction) {
-        let local = RightHandCoordSys::new(self.view.looking_direction);
-        self.view.position += local.direction(direction) * Self::SPEED;
+        self.view.position += Directions::from_orientation(self).get(direction) * self.speed;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn orientation(&self) -> &Orientation {
+        &self.view.orientation
     }
 
     pub fn view_matrix(&self) -> Mat4 {
@@ -274,14 +485,61 @@ impl Camera {
         self.perspective.perspective_matrix()
     }
 
-    pub fn new(perspective: CameraPerspectiveState, view: CameraViewState) -> Self {
-        Self { perspective, view }
+    pub fn projection(&self) -> &Projection {
+        &self.perspective
+    }
+
+    pub fn projection_mut(&mut self) -> &mut Projection {
+        &mut self.perspective
+    }
+
+    /// World space ray `(origin, direction)` through cursor, see `screen_to_ray`.
+    pub fn screen_to_ray(&self, cursor: PhysicalPosition<f64>, viewport: &Viewport) -> (Vec3, Vec3) {
+        screen_to_ray(&self.view_matrix(), &self.perspective_matrix(), cursor, viewport)
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        let rotation = self.view.orientation.quat();
+        CameraPose {
+            position: self.view.position.into(),
+            orientation: [rotation.i, rotation.j, rotation.k, rotation.w],
+            fovy: self.perspective.fovy(),
+        }
+    }
+
+    pub fn set_pose(&mut self, pose: &CameraPose) {
+        let [x, y, z, w] = pose.orientation;
+        self.view.position = pose.position.into();
+        self.view.orientation = Orientation::from_quat(glm::quat(x, y, z, w));
+        if let Some(fovy) = pose.fovy {
+            self.perspective.set_fovy(fovy);
+        }
+    }
+
+    /// Write current pose as ron file.
+    pub fn save(&self, path: &Path) -> Result<(), CameraPoseError> {
+        let source = ron::ser::to_string_pretty(&self.pose(), ron::ser::PrettyConfig::default())
+            .map_err(CameraPoseError::Serialize)?;
+        std::fs::write(path, source)?;
+        Ok(())
+    }
+
+    /// Restore pose written by `save`.
+    pub fn load(&mut self, path: &Path) -> Result<(), CameraPoseError> {
+        let source = std::fs::read_to_string(path)?;
+        let pose = ron::from_str(&source).map_err(CameraPoseError::Deserialize)?;
+        self.set_pose(&pose);
+        Ok(())
+    }
+
+    pub fn new(perspective: Projection, view: CameraViewState) -> Self {
+        Self { perspective, view, speed: Self::SPEED }
     }
 }
 
 impl Default for Camera {
     fn default() -> Self {
-        let perspective = CameraPerspectiveState::default();
+        let perspective = Projection::default();
         let view = CameraViewState::default();
         Self::new(perspective, view)
     }
@@ -0,0 +1,38 @@
+//! Application wide settings, tune behavior here.
+//!
+//! Input speeds are not duplicated here, see `Controls` defaults.
+
+/// Requested OpenGL version as `(major, minor)`, shaders target `#version 450`.
+pub const GL_VERSION: (u8, u8) = (4, 5);
+
+/// Versions tried in order when `GL_VERSION` context can not be created.
+///
+/// Empty since shaders declare `#version 450` and buffers, vertex arrays and textures use 4.5 direct state access,
+/// an older context would fail on the first shader compile or DSA call.
+pub const GL_FALLBACK_VERSIONS: &[(u8, u8)] = &[];
+
+/// Gamma correct output, shaders then operate in linear space and the framebuffer encodes to sRGB.
+pub const SRGB_OUTPUT: bool = false;
+
+/// Bits of depth buffer requested with the context.
+pub const DEPTH_BITS: u8 = 24;
+
+/// Bits of stencil buffer requested with the context.
+pub const STENCIL_BITS: u8 = 8;
+
+pub const WINDOW_TITLE: &str = "3D labyrinth";
+
+/// Number of labyrinth cells along every axis.
+pub const LABYRINTH_GRID_SIZE: usize = 6;
+
+/// Radius of sphere around the origin covered by the sun's shadow map, labyrinth fills `[-1, 1]` cube.
+pub const SHADOW_RADIUS: f32 = 1.8;
+
+/// Radians per second the sun lighting the sphere travels around the vertical axis.
+pub const SUN_ANGULAR_SPEED: f32 = 0.5;
+
+/// Relative to working directory, written on F12.
+pub const SCREENSHOT_PATH: &str = "screenshot.png";
+
+/// Relative to working directory, written on F5 and read on F9.
+pub const CAMERA_POSE_PATH: &str = "camera.ron";
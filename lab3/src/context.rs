@@ -0,0 +1,216 @@
+use std::ffi::{c_void, CStr};
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+
+use gl::types::{GLchar, GLenum, GLint, GLsizei, GLuint};
+use glutin::{Api, ContextBuilder, CreationError, GlProfile, GlRequest, NotCurrent, PossiblyCurrent, WindowedContext};
+use glutin::event_loop::EventLoop;
+use glutin::window::WindowBuilder;
+
+use crate::consts;
+use crate::gl_assert_no_err;
+
+
+/// Context builder requesting core profile of given version.
+///
+/// Debug builds additionally request debug context, see `enable_debug_output`.
+pub fn builder<'a>(version: (u8, u8)) -> ContextBuilder<'a, NotCurrent> {
+    ContextBuilder::new()
+        .with_gl(GlRequest::Specific(Api::OpenGl, version))
+        .with_gl_profile(GlProfile::Core)
+        .with_gl_debug_flag(cfg!(debug_assertions))
+        .with_depth_buffer(consts::DEPTH_BITS)
+        .with_stencil_buffer(consts::STENCIL_BITS)
+        .with_srgb(consts::SRGB_OUTPUT)
+}
+
+#[derive(Debug)]
+pub enum ContextError {
+    /// No requested version could be created, holds error of every attempt.
+    Creation(Vec<((u8, u8), CreationError)>),
+    MakeCurrent(glutin::ContextError),
+    /// Context was created but does not expose core profile.
+    NotCoreProfile((u8, u8)),
+}
+
+impl Display for ContextError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextError::Creation(attempts) => {
+                write!(f, "failed to create OpenGL context")?;
+                for ((major, minor), error) in attempts {
+                    write!(f, "\n\t{}.{}: {}", major, minor, error)?;
+                }
+                Ok(())
+            },
+            ContextError::MakeCurrent(error) => write!(f, "failed to make context current: {}", error),
+            ContextError::NotCoreProfile((major, minor)) => write!(f, "OpenGL {}.{} context is not core profile", major, minor),
+        }
+    }
+}
+
+impl std::error::Error for ContextError { }
+
+impl From<glutin::ContextError> for ContextError {
+    fn from(error: glutin::ContextError) -> Self { ContextError::MakeCurrent(error) }
+}
+
+/// Which OpenGL versions to request, in order of preference.
+#[derive(Debug, Clone)]
+pub struct ContextConfig {
+    pub versions: Vec<(u8, u8)>,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        let mut versions = vec![consts::GL_VERSION];
+        versions.extend_from_slice(consts::GL_FALLBACK_VERSIONS);
+        Self { versions }
+    }
+}
+
+/// Try `create` with every version in order, returns first success together with its version.
+///
+/// Errors of all attempts are returned when none succeeds.
+pub fn first_supported<T, E>(
+    versions: &[(u8, u8)],
+    mut create: impl FnMut((u8, u8)) -> Result<T, E>,
+) -> Result<(T, (u8, u8)), Vec<((u8, u8), E)>> {
+    let mut errors = Vec::new();
+    for &version in versions {
+        match create(version) {
+            Ok(created) => return Ok((created, version)),
+            Err(error) => {
+                log::warn!("OpenGL {}.{} context is not available", version.0, version.1);
+                errors.push((version, error));
+            },
+        }
+    }
+    Err(errors)
+}
+
+/// Current windowed context with loaded gl functions.
+pub struct GlContext {
+    context: WindowedContext<PossiblyCurrent>,
+    version: (u8, u8),
+}
+
+impl GlContext {
+    /// Create window with the first supported version from `config`, make it current and load gl functions.
+    pub fn new<T>(event_loop: &EventLoop<T>, window: WindowBuilder, config: &ContextConfig) -> Result<Self, ContextError> {
+        let (context, version) = first_supported(&config.versions, |version| {
+            builder(version).build_windowed(window.clone(), event_loop)
+        }).map_err(ContextError::Creation)?;
+        let context = unsafe { context.make_current() }.map_err(|(_, error)| error)?;
+        gl::load_with(|ptr| context.get_proc_address(ptr) as *const _);
+        if !is_core_profile() {
+            return Err(ContextError::NotCoreProfile(version));
+        }
+        enable_debug_output();
+        log::info!("Created OpenGL {}.{} core context", version.0, version.1);
+        Ok(Self { context, version })
+    }
+
+    pub fn version(&self) -> (u8, u8) {
+        self.version
+    }
+}
+
+impl Deref for GlContext {
+    type Target = WindowedContext<PossiblyCurrent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.context
+    }
+}
+
+/// Whether current context was created with core profile.
+pub fn is_core_profile() -> bool {
+    let mut mask: GLint = 0;
+    gl_assert_no_err!();
+    unsafe { gl::GetIntegerv(gl::CONTEXT_PROFILE_MASK, &mut mask); }
+    gl_assert_no_err!();
+    mask as GLenum & gl::CONTEXT_CORE_PROFILE_BIT != 0
+}
+
+/// Whether current context is a debug context.
+pub fn is_debug() -> bool {
+    let mut flags: GLint = 0;
+    gl_assert_no_err!();
+    unsafe { gl::GetIntegerv(gl::CONTEXT_FLAGS, &mut flags); }
+    gl_assert_no_err!();
+    flags as GLenum & gl::CONTEXT_FLAG_DEBUG_BIT != 0
+}
+
+/// Whether current context supports extension with given name, e.g. `GL_EXT_texture_filter_anisotropic`.
+pub fn has_extension(name: &str) -> bool {
+    let mut count: GLint = 0;
+    gl_assert_no_err!();
+    unsafe { gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count); }
+    gl_assert_no_err!();
+    (0..count as GLuint).any(|index| {
+        let extension = unsafe { gl::GetStringi(gl::EXTENSIONS, index) };
+        !extension.is_null() && unsafe { CStr::from_ptr(extension as *const GLchar) }.to_bytes() == name.as_bytes()
+    })
+}
+
+/// Forward driver debug messages to the log, does nothing outside of debug context.
+pub fn enable_debug_output() {
+    if !is_debug() {
+        return;
+    }
+    gl_assert_no_err!();
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(debug_callback), std::ptr::null());
+    }
+    gl_assert_no_err!();
+}
+
+extern "system" fn debug_callback(
+    _source: GLenum,
+    _gl_type: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    _length: GLsizei,
+    message: *const GLchar,
+    _user_param: *mut c_void,
+) {
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => log::error!("GL {}: {}", id, message),
+        gl::DEBUG_SEVERITY_MEDIUM => log::warn!("GL {}: {}", id, message),
+        gl::DEBUG_SEVERITY_LOW => log::info!("GL {}: {}", id, message),
+        _ => log::debug!("GL {}: {}", id, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_supported_returns_first_successful_version() {
+        let mut attempted = Vec::new();
+        let created = first_supported(&[(4, 6), (4, 5), (4, 3)], |version| {
+            attempted.push(version);
+            if version <= (4, 5) { Ok(version.1) } else { Err("unsupported") }
+        });
+        assert_eq!(created.unwrap(), (5, (4, 5)));
+        assert_eq!(attempted, [(4, 6), (4, 5)]);
+    }
+
+    #[test]
+    fn first_supported_collects_errors_of_all_attempts() {
+        let created = first_supported(&[(4, 6), (4, 5)], |version| Err::<(), _>(version.1));
+        assert_eq!(created.unwrap_err(), [((4, 6), 6), ((4, 5), 5)]);
+    }
+
+    #[test]
+    fn default_config_requests_only_versions_with_direct_state_access() {
+        let config = ContextConfig::default();
+        assert_eq!(config.versions[0], consts::GL_VERSION);
+        assert!(config.versions.iter().all(|&version| version >= (4, 5)));
+    }
+}
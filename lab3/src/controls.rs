@@ -0,0 +1,87 @@
+/// Input sensitivities read by event handling, adjustable at runtime.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Controls {
+    /// World units travelled by camera per key press.
+    pub move_speed: f32,
+    /// Degrees of rotation per pixel of mouse motion.
+    pub rotate_speed: f32,
+    /// Degrees of field of view per key press or scroll line.
+    pub zoom_speed: f32,
+    /// Multiplier applied to every speed while boost (shift) is held.
+    pub boost_factor: f32,
+    boosted: bool,
+}
+
+impl Controls {
+    pub const DEFAULT_MOVE_SPEED: f32 = 0.05;
+    pub const DEFAULT_ROTATE_SPEED: f32 = 0.5;
+    pub const DEFAULT_ZOOM_SPEED: f32 = 5.0;
+    pub const DEFAULT_BOOST_FACTOR: f32 = 4.0;
+
+    pub fn new(move_speed: f32, rotate_speed: f32, zoom_speed: f32) -> Self {
+        Self { move_speed, rotate_speed, zoom_speed, boost_factor: Self::DEFAULT_BOOST_FACTOR, boosted: false }
+    }
+
+    pub fn set_boosted(&mut self, boosted: bool) {
+        self.boosted = boosted;
+    }
+
+    pub fn is_boosted(&self) -> bool {
+        self.boosted
+    }
+
+    fn scale(&self) -> f32 {
+        if self.boosted { self.boost_factor } else { 1.0 }
+    }
+
+    /// Distance of single movement step.
+    pub fn move_step(&self) -> f32 {
+        self.move_speed * self.scale()
+    }
+
+    /// Rotation in radians for mouse motion by `pixels`.
+    pub fn rotation(&self, pixels: f64) -> f32 {
+        (pixels as f32 * self.rotate_speed * self.scale()).to_radians()
+    }
+
+    /// Change of field of view in degrees for `steps` zoom steps, negative steps zoom out.
+    pub fn zoom(&self, steps: f32) -> f32 {
+        steps * self.zoom_speed * self.scale()
+    }
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MOVE_SPEED, Self::DEFAULT_ROTATE_SPEED, Self::DEFAULT_ZOOM_SPEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controls() -> Controls {
+        Controls { boost_factor: 3.0, ..Controls::new(0.5, 2.0, 10.0) }
+    }
+
+    #[test]
+    fn speeds_are_used_unscaled_without_boost() {
+        let controls = controls();
+        assert_eq!(controls.move_step(), 0.5);
+        assert!((controls.rotation(45.0) - 90f32.to_radians()).abs() < 1e-6);
+        assert_eq!(controls.zoom(-2.0), -20.0);
+    }
+
+    #[test]
+    fn boost_scales_every_speed() {
+        let mut controls = controls();
+        controls.set_boosted(true);
+        assert!(controls.is_boosted());
+        assert_eq!(controls.move_step(), 1.5);
+        assert!((controls.rotation(15.0) - 90f32.to_radians()).abs() < 1e-6);
+        assert_eq!(controls.zoom(1.0), 30.0);
+
+        controls.set_boosted(false);
+        assert_eq!(controls.move_step(), 0.5);
+    }
+}
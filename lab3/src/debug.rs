@@ -0,0 +1,55 @@
+use crate::{binder, vertex, programs, index_buffer, uniform, drawing, painter};
+
+use index_buffer::IndexBufferObject;
+use vertex::{VertexAttribute, BufferObject};
+use binder::Binder;
+use uniform::NamedUniform;
+use drawing::DrawMode;
+use painter::Painter;
+
+use nalgebra_glm as glm;
+
+
+/// Line segments from every position along its normal, two vertices per normal.
+pub fn normal_lines(
+    positions: &[VertexAttribute<f32, 3>],
+    normals: &[VertexAttribute<f32, 3>],
+    length: f32,
+) -> Box<[VertexAttribute<f32, 3>]> {
+    assert_eq!(positions.len(), normals.len(), "every position requires a normal");
+    positions.iter()
+        .zip(normals)
+        .flat_map(|(position, normal)| {
+            let start = glm::make_vec3(position.as_ref());
+            let end = start + glm::make_vec3(normal.as_ref()) * length;
+            [VertexAttribute::from(*start.as_ref()), VertexAttribute::from(*end.as_ref())]
+        })
+        .collect::<Vec<_>>()
+        .into_boxed_slice()
+}
+
+/// Line binder visualizing normals, e.g. output of `geometry::compute_normals`.
+///
+/// Uses `programs::flat_color` so uniforms must provide `perspective_matrix`, `view_matrix` and `color`.
+pub fn normals_binder(
+    positions: &[VertexAttribute<f32, 3>],
+    normals: &[VertexAttribute<f32, 3>],
+    length: f32,
+    uniforms: impl Iterator<Item=NamedUniform>,
+) -> Binder<IndexBufferObject> {
+    let program = programs::flat_color();
+    let lines = BufferObject::create(normal_lines(positions, normals, length));
+    let mut binder = Binder::new(vec!(Box::new(lines)), None, program, uniforms);
+    binder.upload();
+    binder
+}
+
+/// Draw normals immediately with temporary binder, convenient while debugging lighting.
+pub fn draw_normals(
+    positions: &[VertexAttribute<f32, 3>],
+    normals: &[VertexAttribute<f32, 3>],
+    length: f32,
+    uniforms: impl Iterator<Item=NamedUniform>,
+) {
+    Painter::new(normals_binder(positions, normals, length, uniforms), DrawMode::Lines).draw();
+}
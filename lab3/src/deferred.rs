@@ -0,0 +1,117 @@
+use gl::types::GLenum;
+
+use crate::{binder, programs, index_buffer, drawing, painter, framebuffer, render_state, uniform};
+
+use crate::gl_assert_no_err;
+use index_buffer::{IndexBuffer, IndexBufferObject};
+use binder::Binder;
+use drawing::DrawMode;
+use painter::Painter;
+use framebuffer::Framebuffer;
+use render_state::{RenderState, DepthFunc};
+use uniform::{Lighting, Uniform};
+use crate::program::Program;
+
+use nalgebra_glm as glm;
+
+
+/// Deferred shading, geometry is rasterized into the G-buffer and lit per pixel by a full screen pass.
+///
+/// Painters drawn with `draw_geometry` need `perspective_matrix`, `view_matrix` and albedo `vec3` uniforms
+/// at locations 0 to 2, position and normal attributes at locations 0 and 1.
+pub struct DeferredRenderer {
+    gbuffer: Framebuffer,
+    geometry_program: Program,
+    lighting: Lighting,
+    lighting_painter: Painter<IndexBufferObject>,
+}
+
+impl DeferredRenderer {
+    /// World space position.
+    pub const POSITION_ATTACHMENT: usize = 0;
+    /// World space normal, zero for unlit geometry.
+    pub const NORMAL_ATTACHMENT: usize = 1;
+    /// Surface color, alpha marks pixels covered by geometry.
+    pub const ALBEDO_ATTACHMENT: usize = 2;
+    const ATTACHMENT_FORMATS: [GLenum; 3] = [gl::RGBA16F, gl::RGBA16F, gl::RGBA8];
+    const MODEL_MATRIX_LOCATION: gl::types::GLint = 3;
+    const INSTANCED_LOCATION: gl::types::GLint = 4;
+
+    /// G-buffer should match size of the viewport lighting pass draws into, see `resize`.
+    pub fn new(width: u32, height: u32, lighting: Lighting) -> Self {
+        let mut binder = Binder::procedural(3, programs::deferred_lighting(), std::iter::empty());
+        binder.upload();
+        // full screen triangle lies on the far plane, depth of the target is not tested against.
+        let lighting_painter = Painter::new(binder, DrawMode::Triangles)
+            .render_state(RenderState::default().depth_func(DepthFunc::Always));
+        lighting.upload(lighting_painter.binder().program());
+        Self {
+            gbuffer: Framebuffer::with_color_attachments(width, height, &Self::ATTACHMENT_FORMATS),
+            geometry_program: programs::gbuffer(),
+            lighting,
+            lighting_painter,
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if self.gbuffer.size() != (width, height) {
+            self.gbuffer = Framebuffer::with_color_attachments(width, height, &Self::ATTACHMENT_FORMATS);
+        }
+    }
+
+    pub fn gbuffer(&self) -> &Framebuffer {
+        &self.gbuffer
+    }
+
+    pub fn geometry_program(&self) -> &Program {
+        &self.geometry_program
+    }
+
+    pub fn lighting(&self) -> &Lighting {
+        &self.lighting
+    }
+
+    pub fn set_lighting(&mut self, lighting: Lighting) {
+        lighting.upload(self.lighting_painter.binder().program());
+        self.lighting = lighting;
+    }
+
+    /// Clear the G-buffer and run geometry pass `draw` rendering into it, e.g. with `draw_geometry` calls.
+    pub fn geometry_pass(&self, draw: impl FnOnce()) {
+        let _framebuffer_binder = self.gbuffer.scoped_binder();
+        // named clears leave clear color of the context intact.
+        gl_assert_no_err!();
+        unsafe {
+            for attachment in 0..self.gbuffer.color_attachment_count() {
+                gl::ClearNamedFramebufferfv(self.gbuffer.id(), gl::COLOR, attachment as _, [0.0f32; 4].as_ptr());
+            }
+            gl::ClearNamedFramebufferfv(self.gbuffer.id(), gl::DEPTH, 0, &1.0f32);
+        }
+        gl_assert_no_err!();
+        draw();
+    }
+
+    /// Draw painter's world space position, normal and albedo into the G-buffer during `geometry_pass`.
+    pub fn draw_geometry<I: IndexBuffer>(&self, painter: &Painter<I>) {
+        let _program_binder = self.geometry_program.scoped_binder();
+        // model of previously drawn painter must not leak into painters without one.
+        let model = painter.model().copied().unwrap_or_else(glm::Mat4::identity);
+        model.as_ref().bind(Self::MODEL_MATRIX_LOCATION);
+        painter.binder().instance_count().is_some().bind(Self::INSTANCED_LOCATION);
+        painter.draw_with_program(&self.geometry_program);
+    }
+
+    /// Light G-buffer into currently bound framebuffer.
+    pub fn lighting_pass(&self) {
+        for attachment in 0..self.gbuffer.color_attachment_count() {
+            self.gbuffer.bind_color_attachment(attachment, attachment as _);
+        }
+        self.lighting_painter.draw();
+    }
+
+    /// Geometry pass followed by lighting pass.
+    pub fn render(&self, draw: impl FnOnce()) {
+        self.geometry_pass(draw);
+        self.lighting_pass();
+    }
+}
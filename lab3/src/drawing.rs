@@ -1,4 +1,4 @@
-use gl::types::GLenum;
+use gl::types::{GLenum, GLint, GLuint};
 use crate::gl_assert_no_err;
 use crate::index_buffer::IndexType;
 
@@ -29,60 +29,582 @@ impl DrawMode {
     }
 }
 
-pub fn draw_arrays(draw_mode: &DrawMode, vertex_count: usize) {
-    gl_assert_no_err!();
-    log::debug!("Issuing non instanced draw arrays with:\n\tdraw mode: {:?}\n\tvertex count: {}", draw_mode, vertex_count);
-    unsafe {
-        gl::DrawArrays(draw_mode.get(), 0, vertex_count as _);
+/// Opengl polygon rasterization mode enumeration.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PolygonMode {
+    Fill,
+    Line,
+    Point,
+}
+
+impl PolygonMode {
+    /// Get opengl defined numerical value of given variant
+    pub fn get(&self) -> GLenum {
+        match *self {
+            PolygonMode::Fill => gl::FILL,
+            PolygonMode::Line => gl::LINE,
+            PolygonMode::Point => gl::POINT,
+        }
+    }
+}
+
+impl Default for PolygonMode {
+    fn default() -> Self { PolygonMode::Fill }
+}
+
+/// Sets polygon mode for both faces and restores previous mode on drop.
+pub struct PolygonModeScopedBinder {
+    polygon_mode: PolygonMode,
+    previous: GLenum,
+}
+
+impl PolygonModeScopedBinder {
+    pub fn new(polygon_mode: PolygonMode) -> Self {
+        log::debug!("Setting polygon mode {:?}", polygon_mode);
+        // Compatibility contexts report front and back mode separately, core ones keep them equal.
+        let mut previous = [gl::FILL as GLint; 2];
+        gl_assert_no_err!();
+        unsafe {
+            gl::GetIntegerv(gl::POLYGON_MODE, previous.as_mut_ptr());
+            gl::PolygonMode(gl::FRONT_AND_BACK, polygon_mode.get());
+        }
+        gl_assert_no_err!();
+        Self { polygon_mode, previous: previous[0] as _ }
+    }
+}
+
+impl Drop for PolygonModeScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring polygon mode from {:?}", self.polygon_mode);
+        gl_assert_no_err!();
+        unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, self.previous); }
+        gl_assert_no_err!();
+    }
+}
+
+/// Order of vertices of front facing triangles as seen in window space.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Winding {
+    /// Counter clockwise, opengl default and the convention of OBJ and glTF.
+    Ccw,
+    Cw,
+}
+
+impl Winding {
+    /// Get opengl defined numerical value of given variant
+    pub fn get(&self) -> GLenum {
+        match *self {
+            Winding::Ccw => gl::CCW,
+            Winding::Cw => gl::CW,
+        }
+    }
+}
+
+impl Default for Winding {
+    fn default() -> Self { Winding::Ccw }
+}
+
+/// Sets front face winding and restores previous winding on drop.
+pub struct WindingScopedBinder {
+    winding: Winding,
+    previous: GLenum,
+}
+
+impl WindingScopedBinder {
+    pub fn new(winding: Winding) -> Self {
+        log::debug!("Setting front face winding {:?}", winding);
+        let mut previous = gl::CCW as GLint;
+        gl_assert_no_err!();
+        unsafe {
+            gl::GetIntegerv(gl::FRONT_FACE, &mut previous);
+            gl::FrontFace(winding.get());
+        }
+        gl_assert_no_err!();
+        Self { winding, previous: previous as _ }
+    }
+}
+
+impl Drop for WindingScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring front face winding from {:?}", self.winding);
+        gl_assert_no_err!();
+        unsafe { gl::FrontFace(self.previous); }
+        gl_assert_no_err!();
+    }
+}
+
+/// Disables face culling and sets `double_sided` bool uniform at `location` of current program,
+/// restores culling and clears the uniform on drop, see `Painter::double_sided`.
+pub struct DoubleSidedScopedBinder {
+    location: GLint,
+    was_culling: bool,
+}
+
+impl DoubleSidedScopedBinder {
+    pub fn new(location: GLint) -> Self {
+        log::debug!("Disabling face culling for double sided draw");
+        let was_culling = calls::is_enabled(gl::CULL_FACE);
+        calls::set_enabled(gl::CULL_FACE, false);
+        calls::uniform_bool(location, true);
+        Self { location, was_culling }
+    }
+}
+
+impl Drop for DoubleSidedScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring face culling after double sided draw");
+        if self.was_culling {
+            calls::set_enabled(gl::CULL_FACE, true);
+        }
+        calls::uniform_bool(self.location, false);
+    }
+}
+
+/// Sets rasterized line width and restores previous width on drop.
+///
+/// Width is clamped to `GL_ALIASED_LINE_WIDTH_RANGE`, many core profile drivers support only `1.0`
+/// (forward compatible contexts reject anything wider) so thick lines should be expanded into quads
+/// in a geometry shader instead.
+pub struct LineWidthScopedBinder {
+    line_width: f32,
+    previous: f32,
+}
+
+impl LineWidthScopedBinder {
+    pub fn new(line_width: f32) -> Self {
+        let mut range = [1.0f32; 2];
+        let mut previous = 1.0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::GetFloatv(gl::ALIASED_LINE_WIDTH_RANGE, range.as_mut_ptr());
+            gl::GetFloatv(gl::LINE_WIDTH, &mut previous);
+        }
+        let line_width = line_width.clamp(range[0], range[1]);
+        log::debug!("Setting line width {}", line_width);
+        unsafe { gl::LineWidth(line_width); }
+        gl_assert_no_err!();
+        Self { line_width, previous }
+    }
+}
+
+impl Drop for LineWidthScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring line width {} from {}", self.previous, self.line_width);
+        gl_assert_no_err!();
+        unsafe { gl::LineWidth(self.previous); }
+        gl_assert_no_err!();
+    }
+}
+
+/// Sets rasterized point size and restores previous size on drop.
+///
+/// Size set here is ignored while `GL_PROGRAM_POINT_SIZE` is enabled, shader's `gl_PointSize` wins then.
+pub struct PointSizeScopedBinder {
+    point_size: f32,
+    previous: f32,
+}
+
+impl PointSizeScopedBinder {
+    pub fn new(point_size: f32) -> Self {
+        log::debug!("Setting point size {}", point_size);
+        let mut previous = 1.0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::GetFloatv(gl::POINT_SIZE, &mut previous);
+            gl::PointSize(point_size);
+        }
+        gl_assert_no_err!();
+        Self { point_size, previous }
+    }
+}
+
+impl Drop for PointSizeScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring point size {} from {}", self.previous, self.point_size);
+        gl_assert_no_err!();
+        unsafe { gl::PointSize(self.previous); }
+        gl_assert_no_err!();
+    }
+}
+
+/// Lets vertex shader set size of each point through `gl_PointSize`, restores previous state on drop.
+///
+/// Core profile rasterizes every point as sprite so fragment shaders can shape them with `gl_PointCoord`,
+/// see `shaders/point_sprite_f.glsl`.
+pub struct PointSpriteScopedBinder {
+    was_enabled: bool,
+}
+
+impl PointSpriteScopedBinder {
+    pub fn new() -> Self {
+        log::debug!("Enabling program point size");
+        gl_assert_no_err!();
+        let was_enabled = unsafe { gl::IsEnabled(gl::PROGRAM_POINT_SIZE) } == gl::TRUE;
+        unsafe { gl::Enable(gl::PROGRAM_POINT_SIZE); }
+        gl_assert_no_err!();
+        Self { was_enabled }
+    }
+}
+
+impl Drop for PointSpriteScopedBinder {
+    fn drop(&mut self) {
+        if !self.was_enabled {
+            log::debug!("Disabling program point size");
+            gl_assert_no_err!();
+            unsafe { gl::Disable(gl::PROGRAM_POINT_SIZE); }
+            gl_assert_no_err!();
+        }
+    }
+}
+
+/// Window space rectangle, origin in bottom-left corner as in opengl.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Rect {
+    pub x: GLint,
+    pub y: GLint,
+    pub width: GLint,
+    pub height: GLint,
+}
+
+impl Rect {
+    pub fn new(x: GLint, y: GLint, width: GLint, height: GLint) -> Self {
+        Self { x, y, width, height }
+    }
+}
+
+/// Enables scissor test restricted to given rectangle, restores previous scissor test state and box on drop.
+pub struct ScissorScopedBinder {
+    rect: Rect,
+    previous: Rect,
+    was_enabled: bool,
+}
+
+impl ScissorScopedBinder {
+    pub fn new(rect: Rect) -> Self {
+        log::debug!("Enabling scissor test with {:?}", rect);
+        let mut previous = [0; 4];
+        gl_assert_no_err!();
+        let was_enabled = unsafe { gl::IsEnabled(gl::SCISSOR_TEST) } == gl::TRUE;
+        unsafe {
+            gl::GetIntegerv(gl::SCISSOR_BOX, previous.as_mut_ptr());
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(rect.x, rect.y, rect.width, rect.height);
+        }
+        gl_assert_no_err!();
+        let [x, y, width, height] = previous;
+        Self { rect, previous: Rect::new(x, y, width, height), was_enabled }
+    }
+}
+
+impl Drop for ScissorScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring scissor test {:?} from {:?}", self.previous, self.rect);
+        gl_assert_no_err!();
+        unsafe {
+            gl::Scissor(self.previous.x, self.previous.y, self.previous.width, self.previous.height);
+            if !self.was_enabled {
+                gl::Disable(gl::SCISSOR_TEST);
+            }
+        }
+        gl_assert_no_err!();
+    }
+}
+
+/// Instanced part of `DrawCall`, per instance attributes are fetched starting at `base`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Instances {
+    pub count: usize,
+    pub base: usize,
+}
+
+/// Gl function `DrawCall::issue` dispatches to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EntryPoint {
+    DrawArrays,
+    DrawArraysInstanced,
+    DrawArraysInstancedBaseInstance,
+    DrawElements,
+    DrawElementsBaseVertex,
+    DrawElementsInstanced,
+    DrawElementsInstancedBaseVertexBaseInstance,
+}
+
+/// Description of single non indirect draw, see `issue`.
+///
+/// `first` is the first vertex for array draws and the first index for indexed ones.
+#[derive(Debug, Copy, Clone)]
+pub struct DrawCall {
+    pub mode: DrawMode,
+    pub count: usize,
+    pub first: usize,
+    pub index_type: Option<IndexType>,
+    pub instances: Option<Instances>,
+    /// Added to every index, ignored by array draws.
+    pub base_vertex: i32,
+}
+
+impl DrawCall {
+    pub fn arrays(mode: DrawMode, vertex_count: usize) -> Self {
+        Self { mode, count: vertex_count, first: 0, index_type: None, instances: None, base_vertex: 0 }
+    }
+
+    pub fn indexed(mode: DrawMode, index_count: usize, index_type: IndexType) -> Self {
+        Self { index_type: Some(index_type), ..Self::arrays(mode, index_count) }
+    }
+
+    pub fn first(mut self, first: usize) -> Self {
+        self.first = first;
+        self
+    }
+
+    pub fn instanced(mut self, count: usize, base: usize) -> Self {
+        self.instances = Some(Instances { count, base });
+        self
+    }
+
+    pub fn base_vertex(mut self, base_vertex: i32) -> Self {
+        self.base_vertex = base_vertex;
+        self
+    }
+
+    /// Simplest gl function able to issue this draw, base variants require opengl 4.2.
+    pub fn entry_point(&self) -> EntryPoint {
+        match (self.index_type, self.instances) {
+            (None, None) => EntryPoint::DrawArrays,
+            (None, Some(Instances { base: 0, .. })) => EntryPoint::DrawArraysInstanced,
+            (None, Some(_)) => EntryPoint::DrawArraysInstancedBaseInstance,
+            (Some(_), None) if self.base_vertex == 0 => EntryPoint::DrawElements,
+            (Some(_), None) => EntryPoint::DrawElementsBaseVertex,
+            (Some(_), Some(Instances { base: 0, .. })) if self.base_vertex == 0 => EntryPoint::DrawElementsInstanced,
+            (Some(_), Some(_)) => EntryPoint::DrawElementsInstancedBaseVertexBaseInstance,
+        }
+    }
+
+    pub fn issue(&self) {
+        log::debug!("Issuing {:?} with {:?}", self.entry_point(), self);
+        let mode = self.mode.get();
+        let count = self.count as _;
+        let (instance_count, base_instance) = self.instances
+            .map_or((1, 0), |instances| (instances.count as _, instances.base as _));
+        let (index_type, indices) = self.index_type.map_or((0, std::ptr::null()), |index_type| {
+            (index_type.get_gl_type(), (self.first * index_type.size_bytes()) as *const std::ffi::c_void)
+        });
+        gl_assert_no_err!();
+        unsafe {
+            match self.entry_point() {
+                EntryPoint::DrawArrays => gl::DrawArrays(mode, self.first as _, count),
+                EntryPoint::DrawArraysInstanced => gl::DrawArraysInstanced(mode, self.first as _, count, instance_count),
+                EntryPoint::DrawArraysInstancedBaseInstance => gl::DrawArraysInstancedBaseInstance(
+                    mode, self.first as _, count, instance_count, base_instance,
+                ),
+                EntryPoint::DrawElements => gl::DrawElements(mode, count, index_type, indices),
+                EntryPoint::DrawElementsBaseVertex => gl::DrawElementsBaseVertex(
+                    mode, count, index_type, indices, self.base_vertex,
+                ),
+                EntryPoint::DrawElementsInstanced => gl::DrawElementsInstanced(
+                    mode, count, index_type, indices, instance_count,
+                ),
+                EntryPoint::DrawElementsInstancedBaseVertexBaseInstance => gl::DrawElementsInstancedBaseVertexBaseInstance(
+                    mode, count, index_type, indices, instance_count, self.base_vertex, base_instance,
+                ),
+            }
+        }
+        gl_assert_no_err!();
+    }
+}
+
+/// Layout of single command consumed by `glMultiDrawElementsIndirect`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct DrawElementsIndirectCommand {
+    pub count: GLuint,
+    pub instance_count: GLuint,
+    pub first_index: GLuint,
+    pub base_vertex: GLint,
+    pub base_instance: GLuint,
+}
+
+/// Gpu buffer holding indirect draw commands.
+pub struct IndirectBuffer {
+    id: GLuint,
+    command_count: usize,
+}
+
+impl IndirectBuffer {
+    pub fn create(commands: &[DrawElementsIndirectCommand]) -> Self {
+        let mut id = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateBuffers(1, &mut id);
+            gl::NamedBufferData(
+                id,
+                std::mem::size_of_val(commands) as _,
+                commands.as_ptr() as *const std::ffi::c_void,
+                gl::STATIC_DRAW,
+            );
+        }
+        gl_assert_no_err!();
+        Self { id, command_count: commands.len() }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn command_count(&self) -> usize {
+        self.command_count
+    }
+}
+
+impl Drop for IndirectBuffer {
+    fn drop(&mut self) {
+        log::debug!("Deleting indirect buffer {}", self.id);
+        unsafe { gl::DeleteBuffers(1, &self.id); }
     }
-    gl_assert_no_err!();
 }
 
-pub fn draw_indexed(draw_mode: &DrawMode, vertex_count: usize, index_type: &IndexType) {
+pub fn draw_indexed_indirect(draw_mode: &DrawMode, index_type: &IndexType, commands: &IndirectBuffer) {
     gl_assert_no_err!();
-    log::debug!("Issuing non instanced index draw with:\n\tdraw mode: {:?}\n\tvertex count: {}", draw_mode, vertex_count);
+    log::debug!("Issuing indirect indexed draw with:\n\tdraw mode: {:?}\n\tcommand count: {}", draw_mode, commands.command_count());
+    let mut previous = 0;
     unsafe {
-        gl::DrawElements(
+        gl::GetIntegerv(gl::DRAW_INDIRECT_BUFFER_BINDING, &mut previous);
+        gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, commands.id());
+        gl::MultiDrawElementsIndirect(
             draw_mode.get(),
-            vertex_count as _,
             index_type.get_gl_type(),
-            std::ptr::null()
+            std::ptr::null(),
+            commands.command_count() as _,
+            0,
         );
+        gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, previous as _);
     }
     gl_assert_no_err!();
 }
 
-pub mod instanced {
+#[cfg(not(test))]
+mod calls {
+    use gl::types::{GLenum, GLint};
     use crate::gl_assert_no_err;
-    use super::DrawMode;
-    use crate::index_buffer::IndexType;
 
-    pub fn draw_arrays(draw_mode: &DrawMode, vertex_count: usize, instance_count: usize) {
+    pub fn is_enabled(capability: GLenum) -> bool {
         gl_assert_no_err!();
-        log::debug!("Issuing instanced draw arrays with:\n\tdraw mode: {:?}\n\tvertex count: {}", draw_mode, vertex_count);
-        unsafe {
-            gl::DrawArraysInstanced(
-                draw_mode.get(),
-                0,
-                3 as _, // vertex_count as
-                instance_count as _,
-            );
-        }
+        let enabled = unsafe { gl::IsEnabled(capability) } == gl::TRUE;
         gl_assert_no_err!();
+        enabled
     }
 
-    pub fn draw_indexed(draw_mode: &DrawMode, vertex_count: usize, index_type: &IndexType, instance_count: usize) {
+    pub fn set_enabled(capability: GLenum, enabled: bool) {
         gl_assert_no_err!();
-        log::debug!("Issuing instanced indexed draw with:\n\tdraw mode: {:?}\n\tvertex count: {}", draw_mode, vertex_count);
         unsafe {
-            gl::DrawElementsInstanced(
-                draw_mode.get(),
-                vertex_count as _,
-                index_type.get_gl_type(),
-                std::ptr::null(),
-                instance_count as _
-            );
+            if enabled {
+                gl::Enable(capability);
+            } else {
+                gl::Disable(capability);
+            }
         }
         gl_assert_no_err!();
     }
-}
\ No newline at end of file
+
+    pub fn uniform_bool(location: GLint, value: bool) {
+        gl_assert_no_err!();
+        unsafe { gl::Uniform1i(location, value as _); }
+        gl_assert_no_err!();
+    }
+}
+
+#[cfg(test)]
+pub mod calls {
+    use std::cell::RefCell;
+    use gl::types::{GLenum, GLint};
+
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub enum Call {
+        Enable(GLenum),
+        Disable(GLenum),
+        UniformBool(GLint, bool),
+    }
+
+    thread_local! {
+        static ENABLED: RefCell<Vec<GLenum>> = RefCell::new(Vec::new());
+        static ISSUED: RefCell<Vec<Call>> = RefCell::new(Vec::new());
+    }
+
+    pub fn is_enabled(capability: GLenum) -> bool {
+        ENABLED.with(|enabled| enabled.borrow().contains(&capability))
+    }
+
+    pub fn set_enabled(capability: GLenum, enabled: bool) {
+        ENABLED.with(|capabilities| {
+            let mut capabilities = capabilities.borrow_mut();
+            capabilities.retain(|&enabled| enabled != capability);
+            if enabled {
+                capabilities.push(capability);
+            }
+        });
+        let call = if enabled { Call::Enable(capability) } else { Call::Disable(capability) };
+        ISSUED.with(|issued| issued.borrow_mut().push(call));
+    }
+
+    pub fn uniform_bool(location: GLint, value: bool) {
+        ISSUED.with(|issued| issued.borrow_mut().push(Call::UniformBool(location, value)));
+    }
+
+    /// Calls issued on current thread since the last `take`.
+    pub fn take() -> Vec<Call> {
+        ISSUED.with(|issued| issued.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calls::Call;
+
+    #[test]
+    fn double_sided_draw_disables_culling_and_flags_uniform_until_dropped() {
+        calls::set_enabled(gl::CULL_FACE, true);
+        calls::take();
+        {
+            let _double_sided_binder = DoubleSidedScopedBinder::new(5);
+            assert!(!calls::is_enabled(gl::CULL_FACE));
+            assert_eq!(calls::take(), [Call::Disable(gl::CULL_FACE), Call::UniformBool(5, true)]);
+        }
+        assert!(calls::is_enabled(gl::CULL_FACE));
+        assert_eq!(calls::take(), [Call::Enable(gl::CULL_FACE), Call::UniformBool(5, false)]);
+    }
+
+    #[test]
+    fn double_sided_draw_keeps_culling_disabled_if_it_was() {
+        drop(DoubleSidedScopedBinder::new(5));
+        assert!(!calls::is_enabled(gl::CULL_FACE));
+        assert_eq!(calls::take(), [Call::Disable(gl::CULL_FACE), Call::UniformBool(5, true), Call::UniformBool(5, false)]);
+    }
+
+    #[test]
+    fn array_draws_use_base_instance_variant_only_when_needed() {
+        let draw = DrawCall::arrays(DrawMode::Triangles, 36);
+        assert_eq!(draw.entry_point(), EntryPoint::DrawArrays);
+        assert_eq!(draw.instanced(4, 0).entry_point(), EntryPoint::DrawArraysInstanced);
+        assert_eq!(draw.instanced(4, 2).entry_point(), EntryPoint::DrawArraysInstancedBaseInstance);
+    }
+
+    #[test]
+    fn array_draws_ignore_base_vertex() {
+        let draw = DrawCall::arrays(DrawMode::Points, 8).base_vertex(3);
+        assert_eq!(draw.entry_point(), EntryPoint::DrawArrays);
+    }
+
+    #[test]
+    fn indexed_draws_use_base_vertex_variant_only_when_needed() {
+        let draw = DrawCall::indexed(DrawMode::Triangles, 36, IndexType::U16);
+        assert_eq!(draw.entry_point(), EntryPoint::DrawElements);
+        assert_eq!(draw.base_vertex(8).entry_point(), EntryPoint::DrawElementsBaseVertex);
+        assert_eq!(draw.instanced(4, 0).entry_point(), EntryPoint::DrawElementsInstanced);
+        assert_eq!(
+            draw.instanced(4, 0).base_vertex(8).entry_point(),
+            EntryPoint::DrawElementsInstancedBaseVertexBaseInstance,
+        );
+        assert_eq!(draw.instanced(4, 1).entry_point(), EntryPoint::DrawElementsInstancedBaseVertexBaseInstance);
+    }
+}
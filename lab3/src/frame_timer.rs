@@ -0,0 +1,93 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+
+/// Accumulates frame times and reports averaged frame rate once per interval.
+pub struct FrameTimer {
+    last_frame: Instant,
+    accumulated: Duration,
+    frames: u32,
+    report_interval: Duration,
+    print_in_place: bool,
+}
+
+impl FrameTimer {
+    pub const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now(), Self::DEFAULT_REPORT_INTERVAL)
+    }
+
+    pub fn starting_at(start: Instant, report_interval: Duration) -> Self {
+        Self {
+            last_frame: start,
+            accumulated: Duration::ZERO,
+            frames: 0,
+            report_interval,
+            print_in_place: false,
+        }
+    }
+
+    /// Print reports to stdout overwriting previous one with `\r` instead of logging them.
+    pub fn print_in_place(mut self, enabled: bool) -> Self {
+        self.print_in_place = enabled;
+        self
+    }
+
+    /// Record frame presented at `now`, returns averaged frame rate once `report_interval` worth of frames passed.
+    pub fn tick_at(&mut self, now: Instant) -> Option<f32> {
+        self.accumulated += now.saturating_duration_since(self.last_frame);
+        self.last_frame = now;
+        self.frames += 1;
+        if self.accumulated < self.report_interval {
+            return None;
+        }
+        let fps = self.frames as f32 / self.accumulated.as_secs_f32();
+        self.accumulated = Duration::ZERO;
+        self.frames = 0;
+        Some(fps)
+    }
+
+    /// Record frame presented now and report frame rate if it is due.
+    pub fn frame(&mut self) {
+        if let Some(fps) = self.tick_at(Instant::now()) {
+            if self.print_in_place {
+                print!("\r{:.0} fps", fps);
+                std::io::stdout().flush().unwrap();
+            } else {
+                log::info!("{:.0} fps", fps);
+            }
+        }
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_average_frame_rate_once_interval_passed() {
+        let start = Instant::now();
+        let mut timer = FrameTimer::starting_at(start, Duration::from_secs(1));
+        for frame in 1..60 {
+            assert_eq!(timer.tick_at(start + Duration::from_millis(frame * 1000 / 60)), None);
+        }
+        let fps = timer.tick_at(start + Duration::from_secs(1)).unwrap();
+        assert!((fps - 60.0).abs() < 1e-3, "{}", fps);
+    }
+
+    #[test]
+    fn report_resets_accumulated_frames() {
+        let start = Instant::now();
+        let mut timer = FrameTimer::starting_at(start, Duration::from_secs(1));
+        assert_eq!(timer.tick_at(start + Duration::from_secs(2)), Some(0.5));
+        assert_eq!(timer.tick_at(start + Duration::from_millis(2500)), None);
+        assert_eq!(timer.tick_at(start + Duration::from_secs(3)), Some(2.0));
+    }
+}
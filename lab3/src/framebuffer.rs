@@ -0,0 +1,207 @@
+use gl::types::{GLenum, GLuint};
+use crate::gl_assert_no_err;
+
+
+/// Toggle linear to sRGB conversion of values written into sRGB capable framebuffers.
+///
+/// With conversion enabled shaders must output linear colors, lighting math is then physically correct
+/// and the hardware applies gamma on write. Clear colors are treated as linear as well.
+pub fn set_srgb_conversion(enabled: bool) {
+    log::debug!("Setting framebuffer sRGB conversion {}", enabled);
+    gl_assert_no_err!();
+    unsafe {
+        if enabled {
+            gl::Enable(gl::FRAMEBUFFER_SRGB);
+        } else {
+            gl::Disable(gl::FRAMEBUFFER_SRGB);
+        }
+    }
+    gl_assert_no_err!();
+}
+
+pub fn srgb_conversion_enabled() -> bool {
+    unsafe { gl::IsEnabled(gl::FRAMEBUFFER_SRGB) == gl::TRUE }
+}
+
+/// Decode single sRGB encoded color channel to linear space.
+pub fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Off screen render target with color textures and depth renderbuffer.
+///
+/// Fragment shader output `layout(location = i)` is written into color attachment `i`, which allows writing
+/// several values in one pass (multiple render targets), e.g. position, normal and albedo of a G-buffer.
+pub struct Framebuffer {
+    id: GLuint,
+    colors: Vec<GLuint>,
+    depth: GLuint,
+    size: (u32, u32),
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32, color_format: GLenum) -> Self {
+        Self::with_color_attachments(width, height, &[color_format])
+    }
+
+    /// Framebuffer with one color texture of given internal format per attachment, all of them are draw buffers.
+    pub fn with_color_attachments(width: u32, height: u32, color_formats: &[GLenum]) -> Self {
+        assert!(!color_formats.is_empty(), "framebuffer requires at least one color attachment");
+        assert!(
+            color_formats.len() <= Self::max_color_attachments(),
+            "{} color attachments exceed limit of {}", color_formats.len(), Self::max_color_attachments()
+        );
+        let mut id = 0;
+        let mut colors = vec![0; color_formats.len()];
+        let mut depth = 0;
+        let draw_buffers = (0..color_formats.len())
+            .map(|index| gl::COLOR_ATTACHMENT0 + index as GLenum)
+            .collect::<Vec<_>>();
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateFramebuffers(1, &mut id);
+
+            gl::CreateTextures(gl::TEXTURE_2D, colors.len() as _, colors.as_mut_ptr());
+            for ((&color, &format), &attachment) in colors.iter().zip(color_formats).zip(&draw_buffers) {
+                gl::TextureStorage2D(color, 1, format, width as _, height as _);
+                gl::TextureParameteri(color, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+                gl::TextureParameteri(color, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+                gl::NamedFramebufferTexture(id, attachment, color, 0);
+            }
+            gl::NamedFramebufferDrawBuffers(id, draw_buffers.len() as _, draw_buffers.as_ptr());
+
+            gl::CreateRenderbuffers(1, &mut depth);
+            gl::NamedRenderbufferStorage(depth, gl::DEPTH_COMPONENT24, width as _, height as _);
+            gl::NamedFramebufferRenderbuffer(id, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, depth);
+        }
+        gl_assert_no_err!();
+        let framebuffer = Self { id, colors, depth, size: (width, height) };
+        assert!(framebuffer.is_complete(), "framebuffer {} is incomplete", id);
+        framebuffer
+    }
+
+    /// Number of color attachments that can be written at once in current context.
+    pub fn max_color_attachments() -> usize {
+        let mut max_draw_buffers = 0;
+        let mut max_attachments = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_DRAW_BUFFERS, &mut max_draw_buffers);
+            gl::GetIntegerv(gl::MAX_COLOR_ATTACHMENTS, &mut max_attachments);
+        }
+        max_draw_buffers.min(max_attachments) as _
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Texture of the first color attachment.
+    pub fn color_texture(&self) -> GLuint {
+        self.colors[0]
+    }
+
+    pub fn color_attachment(&self, index: usize) -> GLuint {
+        self.colors[index]
+    }
+
+    pub fn color_attachment_count(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Bind texture of given color attachment for sampling, e.g. G-buffer in lighting pass.
+    pub fn bind_color_attachment(&self, index: usize, unit: GLuint) {
+        gl_assert_no_err!();
+        unsafe { gl::BindTextureUnit(unit, self.colors[index]); }
+        gl_assert_no_err!();
+    }
+
+    /// Copy whole color attachment into `pixels` converted to given pixel `format` and `pixel_type`.
+    ///
+    /// Rows start at the bottom of the texture, `pixels` must fit all of them.
+    pub fn read_color_attachment<T: Copy>(&self, index: usize, format: GLenum, pixel_type: GLenum, pixels: &mut [T]) {
+        gl_assert_no_err!();
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::GetTextureImage(
+                self.colors[index],
+                0,
+                format,
+                pixel_type,
+                std::mem::size_of_val(pixels) as _,
+                pixels.as_mut_ptr() as *mut std::ffi::c_void,
+            );
+        }
+        gl_assert_no_err!();
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn is_complete(&self) -> bool {
+        let status = unsafe { gl::CheckNamedFramebufferStatus(self.id, gl::FRAMEBUFFER) };
+        status == gl::FRAMEBUFFER_COMPLETE
+    }
+
+    /// Bind framebuffer and set viewport to cover it, both are restored on drop.
+    pub fn scoped_binder(&self) -> ScopedBinder {
+        ScopedBinder::new(self.id, self.size)
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        log::debug!("Deleting framebuffer {}", self.id);
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+            gl::DeleteTextures(self.colors.len() as _, self.colors.as_ptr());
+            gl::DeleteRenderbuffers(1, &self.depth);
+        }
+    }
+}
+
+/// Binds framebuffer for drawing and reading, restores previous bindings and viewport on drop so that
+/// offscreen passes can nest.
+pub struct ScopedBinder {
+    id: GLuint,
+    previous_draw: GLuint,
+    previous_read: GLuint,
+    previous_viewport: [gl::types::GLint; 4],
+}
+
+impl ScopedBinder {
+    pub fn new(id: GLuint, size: (u32, u32)) -> Self {
+        log::debug!("Binding framebuffer {}", id);
+        let mut previous_draw = 0;
+        let mut previous_read = 0;
+        let mut previous_viewport = [0; 4];
+        gl_assert_no_err!();
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw);
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+            gl::Viewport(0, 0, size.0 as _, size.1 as _);
+        }
+        gl_assert_no_err!();
+        Self { id, previous_draw: previous_draw as _, previous_read: previous_read as _, previous_viewport }
+    }
+}
+
+impl Drop for ScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Unbinding framebuffer {}, restoring {}", self.id, self.previous_draw);
+        let [x, y, width, height] = self.previous_viewport;
+        gl_assert_no_err!();
+        unsafe {
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.previous_draw);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.previous_read);
+            gl::Viewport(x, y, width, height);
+        }
+        gl_assert_no_err!();
+    }
+}
@@ -0,0 +1,76 @@
+use crate::vertex::VertexAttribute;
+
+use nalgebra_glm as glm;
+
+
+/// Axis aligned bounding box.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: glm::Vec3, max: glm::Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest box containing all positions, `None` for empty slice.
+    pub fn from_positions(positions: &[VertexAttribute<f32, 3>]) -> Option<Self> {
+        let mut points = positions.iter().map(|position| glm::make_vec3(position.as_ref()));
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, point| Self::new(
+            glm::min2(&aabb.min, &point),
+            glm::max2(&aabb.max, &point),
+        )))
+    }
+
+    pub fn center(&self) -> glm::Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Half of box size along each axis.
+    pub fn extents(&self) -> glm::Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    pub fn contains(&self, point: &glm::Vec3) -> bool {
+        (0..3).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+
+    /// Box enclosing this box after transformation, e.g. by painter's model matrix.
+    pub fn transformed(&self, matrix: &glm::Mat4) -> Self {
+        let center = (matrix * glm::vec4(self.center().x, self.center().y, self.center().z, 1.0)).xyz();
+        let linear = glm::mat4_to_mat3(matrix).abs();
+        let extents = linear * self.extents();
+        Self::new(center - extents, center + extents)
+    }
+
+    /// Distance along the ray to the first intersection, `None` if ray misses the box (slab test).
+    ///
+    /// Rays starting inside the box intersect it at distance 0.
+    pub fn intersects_ray(&self, origin: &glm::Vec3, direction: &glm::Vec3) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+        for axis in 0..3 {
+            if direction[axis].abs() <= f32::EPSILON {
+                if origin[axis] < self.min[axis] || origin[axis] > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let inverse = 1.0 / direction[axis];
+            let mut t0 = (self.min[axis] - origin[axis]) * inverse;
+            let mut t1 = (self.max[axis] - origin[axis]) * inverse;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            near = near.max(t0);
+            far = far.min(t1);
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+}
@@ -0,0 +1,85 @@
+use crate::vertex::VertexAttribute;
+
+use nalgebra_glm as glm;
+
+
+/// Capsule along y axis centered at the origin, see `sp` for the output format.
+///
+/// `height` is the distance between centers of the hemispherical caps, i.e. length of the cylindrical middle,
+/// which matches `colliders::capsule::Capsule` segment. Each cap has `rings` rows of quads and the middle
+/// is stitched between cap equators which share horizontal normals, so shading is continuous.
+/// Produces `2 * (rings + 1) * (segments + 1)` vertices and `12 * segments * rings` indices.
+pub fn capsule(
+    radius: f32,
+    height: f32,
+    segments: usize,
+    rings: usize,
+) -> (Box<[VertexAttribute<f32, 3>]>, Box<[VertexAttribute<f32, 3>]>, Box<[u16]>) {
+    use std::f32::consts::PI;
+
+    assert!(segments >= 3 && rings >= 1, "capsule requires at least 3 segments and 1 ring");
+    let rows = 2 * (rings + 1);
+    assert!(rows * (segments + 1) <= u16::MAX as usize + 1, "capsule tessellation exceeds 16 bit indices");
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    // top cap from pole down to equator followed by bottom cap from equator down to pole.
+    for row in 0..rows {
+        let (ring, center) = if row <= rings {
+            (row, height / 2.0)
+        } else {
+            (row - 1, -height / 2.0)
+        };
+        let latitude = PI / 2.0 - ring as f32 * PI / (2 * rings) as f32;
+        for segment in 0..=segments {
+            let longitude = segment as f32 * 2.0 * PI / segments as f32;
+            let normal = glm::vec3(
+                f32::cos(latitude) * f32::cos(longitude),
+                f32::sin(latitude),
+                -f32::cos(latitude) * f32::sin(longitude),
+            );
+            let position = normal * radius + glm::vec3(0.0, center, 0.0);
+            vertices.push(VertexAttribute::from(*position.as_ref()));
+            normals.push(VertexAttribute::from(*normal.as_ref()));
+        }
+    }
+
+    let mut indices = Vec::new();
+    for row in 0..rows - 1 {
+        let k1 = (row * (segments + 1)) as u16;
+        let k2 = k1 + segments as u16 + 1;
+        for (k1, k2) in (k1..).zip(k2..).take(segments) {
+            // triangles touching the poles would be degenerate.
+            if row != 0 {
+                indices.extend([k1, k2, k1 + 1]);
+            }
+            if row != rows - 2 {
+                indices.extend([k1 + 1, k2, k2 + 1]);
+            }
+        }
+    }
+
+    (vertices.into_boxed_slice(), normals.into_boxed_slice(), indices.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capsule_vertex_and_index_counts() {
+        let (vertices, normals, indices) = capsule(1.0, 2.0, 12, 4);
+        assert_eq!(vertices.len(), 2 * 5 * 13);
+        assert_eq!(normals.len(), vertices.len());
+        assert_eq!(indices.len(), 12 * 12 * 4);
+        assert!(indices.iter().all(|&index| (index as usize) < vertices.len()));
+    }
+
+    #[test]
+    fn capsule_normals_are_unit_length() {
+        let (_, normals, _) = capsule(0.5, 1.0, 8, 3);
+        for normal in normals.iter() {
+            assert!((glm::make_vec3(normal.as_ref()).norm() - 1.0).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::vertex::VertexAttribute;
+
+use nalgebra_glm as glm;
+
+
+/// Cone with base of given radius centered at the origin and apex at `(0, height, 0)`, see `sp` for the output format.
+///
+/// Side and base do not share vertices so that the rim keeps a sharp edge.
+/// Produces `3 * (segments + 1) + 1` vertices and `6 * segments` indices.
+pub fn cone(radius: f32, height: f32, segments: usize) -> (Box<[VertexAttribute<f32, 3>]>, Box<[VertexAttribute<f32, 3>]>, Box<[u16]>) {
+    use std::f32::consts::PI;
+
+    assert!(segments >= 3, "cone requires at least 3 segments");
+    assert!(3 * (segments + 1) + 1 <= u16::MAX as usize + 1, "{} segments exceed 16 bit indices", segments);
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut indices = Vec::new();
+    let rim = |segment: usize| {
+        let angle = segment as f32 * 2.0 * PI / segments as f32;
+        (f32::cos(angle), f32::sin(angle))
+    };
+
+    // side, apex is duplicated per segment so that every slice has its own normal.
+    for segment in 0..=segments {
+        let (cos, sin) = rim(segment);
+        let normal = glm::vec3(height * cos, radius, height * sin).normalize();
+        vertices.push(VertexAttribute::from([radius * cos, 0.0, radius * sin]));
+        vertices.push(VertexAttribute::from([0.0, height, 0.0]));
+        normals.push(VertexAttribute::from(*normal.as_ref()));
+        normals.push(VertexAttribute::from(*normal.as_ref()));
+    }
+    for segment in 0..segments as u16 {
+        let base = 2 * segment;
+        indices.extend([base, base + 1, base + 2]);
+    }
+
+    // base facing down.
+    let center = vertices.len() as u16;
+    vertices.push(VertexAttribute::from([0.0, 0.0, 0.0]));
+    normals.push(VertexAttribute::from([0.0, -1.0, 0.0]));
+    for segment in 0..=segments {
+        let (cos, sin) = rim(segment);
+        vertices.push(VertexAttribute::from([radius * cos, 0.0, radius * sin]));
+        normals.push(VertexAttribute::from([0.0, -1.0, 0.0]));
+    }
+    for segment in 0..segments as u16 {
+        indices.extend([center, center + 1 + segment, center + 2 + segment]);
+    }
+
+    (vertices.into_boxed_slice(), normals.into_boxed_slice(), indices.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cone_vertex_and_index_counts() {
+        let (vertices, normals, indices) = cone(1.0, 2.0, 16);
+        assert_eq!(vertices.len(), 3 * 17 + 1);
+        assert_eq!(normals.len(), vertices.len());
+        assert_eq!(indices.len(), 6 * 16);
+        assert!(indices.iter().all(|&index| (index as usize) < vertices.len()));
+    }
+
+    #[test]
+    fn cone_normals_are_unit_length() {
+        let (_, normals, _) = cone(0.5, 3.0, 8);
+        for normal in normals.iter() {
+            assert!((glm::make_vec3(normal.as_ref()).norm() - 1.0).abs() < 1e-5);
+        }
+    }
+}
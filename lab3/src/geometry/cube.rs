@@ -1,12 +1,31 @@
 use crate::{binder, vertex, program, index_buffer, uniform, attributes};
 
-use program::Program;
+use program::{Program, ProgramError};
 use index_buffer::IndexBufferObject;
 use binder::Binder;
 use uniform::NamedUniform;
+use super::Aabb;
 
+use nalgebra_glm as glm;
 
-pub fn cube(uniforms: impl Iterator<Item=NamedUniform>) -> Binder<IndexBufferObject<u8>> {
+
+const CUBE_INDICES: [u8; 36] = [
+    0, 1, 5,
+    0, 5, 4,
+    0, 1, 3,
+    0, 3, 2,
+    0, 4, 6,
+    0, 6, 2,
+    1, 5, 7,
+    1, 7, 3,
+    5, 6, 4,
+    5, 7, 6,
+    7, 3, 2,
+    2, 7, 6
+];
+
+/// Unit cube spanning `[-1, 1]` on every axis together with its bounds.
+pub fn cube(uniforms: impl Iterator<Item=NamedUniform>) -> Result<(Binder<IndexBufferObject<u8>>, Aabb), ProgramError> {
     let vertices = attributes!(
          (-1.0, -1.0, -1.0),    // 000
          (-1.0, -1.0,  1.0),    // 001
@@ -29,33 +48,49 @@ pub fn cube(uniforms: impl Iterator<Item=NamedUniform>) -> Binder<IndexBufferObj
         (0.0, 0.4, 0.73f32),
     );
 
-    let indices = Box::new([
-        0, 1, 5,
-        0, 5, 4,
-        0, 1, 3,
-        0, 3, 2,
-        0, 4, 6,
-        0, 6, 2,
-        1, 5, 7,
-        1, 7, 3,
-        5, 6, 4,
-        5, 7, 6,
-        7, 3, 2,
-        2, 7, 6
-    ]);
-
-    let index_buf = IndexBufferObject::create(indices);
+    let index_buf = IndexBufferObject::create(Box::new(CUBE_INDICES));
     let program = Program::from_file(
-        "shaders/cube_v.glsl".as_ref(),
-        "shaders/cube_f.glsl".as_ref(),
+        "cube_v.glsl".as_ref(),
+        "cube_f.glsl".as_ref(),
+    )?;
+
+    let mut binder = Binder::builder()
+        .add_named_vbo("position", vertices)
+        .add_named_vbo("color", colors)
+        .index_buffer(index_buf)
+        .program(program)
+        .add_uniforms(uniforms)
+        .build();
+    binder.upload();
+    let bounds = Aabb::new(glm::vec3(-1.0, -1.0, -1.0), glm::vec3(1.0, 1.0, 1.0));
+    Ok((binder, bounds))
+}
+
+/// Unit cube whose positions double as cube map sampling directions.
+pub fn skybox(uniforms: impl Iterator<Item=NamedUniform>) -> Result<Binder<IndexBufferObject<u8>>, ProgramError> {
+    let vertices = attributes!(
+         (-1.0, -1.0, -1.0),
+         (-1.0, -1.0,  1.0),
+         (-1.0,  1.0, -1.0),
+         (-1.0,  1.0,  1.0),
+         ( 1.0, -1.0, -1.0),
+         ( 1.0, -1.0,  1.0),
+         ( 1.0,  1.0, -1.0),
+         ( 1.0,  1.0,  1.0f32),
     );
 
+    let index_buf = IndexBufferObject::create(Box::new(CUBE_INDICES));
+    let program = Program::from_file(
+        "skybox_v.glsl".as_ref(),
+        "skybox_f.glsl".as_ref(),
+    )?;
+
     let mut binder = Binder::new(
-        vec!(Box::new(vertices), Box::new(colors)),
+        vec!(Box::new(vertices)),
         Some(index_buf),
         program,
         uniforms
     );
     binder.upload();
-    binder
+    Ok(binder)
 }
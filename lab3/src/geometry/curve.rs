@@ -0,0 +1,105 @@
+use crate::vertex::VertexAttribute;
+
+use nalgebra_glm as glm;
+
+
+/// Catmull-Rom spline passing through all control points sampled into a `DrawMode::LineStrip` polyline.
+///
+/// `samples` points (at least 2) are spread uniformly over the curve parameter, first and last sample
+/// coincide with the first and last control point. End tangents are computed by mirroring the neighbouring point.
+pub fn sample_curve(control_points: &[glm::Vec3], samples: usize) -> Box<[VertexAttribute<f32, 3>]> {
+    assert!(control_points.len() >= 2, "curve requires at least 2 control points");
+    assert!(samples >= 2, "curve requires at least 2 samples");
+    let last = control_points.len() - 1;
+    let point = |index: isize| -> glm::Vec3 {
+        match index {
+            index if index < 0 => 2.0 * control_points[0] - control_points[1],
+            index if index as usize > last => 2.0 * control_points[last] - control_points[last - 1],
+            index => control_points[index as usize],
+        }
+    };
+    sample(samples, last, |segment, t| {
+        let segment = segment as isize;
+        catmull_rom(&point(segment - 1), &point(segment), &point(segment + 1), &point(segment + 2), t)
+    })
+}
+
+/// Chain of cubic Bezier segments sampled into a `DrawMode::LineStrip` polyline.
+///
+/// Consecutive segments share end points so `control_points` holds `3 * segments + 1` points:
+/// `p0, c0, c1, p1, c2, c3, p2, ...`.
+pub fn sample_bezier(control_points: &[glm::Vec3], samples: usize) -> Box<[VertexAttribute<f32, 3>]> {
+    assert!(
+        control_points.len() >= 4 && (control_points.len() - 1) % 3 == 0,
+        "cubic bezier chain requires 3 * segments + 1 control points, got {}", control_points.len()
+    );
+    assert!(samples >= 2, "curve requires at least 2 samples");
+    sample(samples, (control_points.len() - 1) / 3, |segment, t| {
+        let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|offset| control_points[3 * segment + offset]);
+        cubic_bezier(&p0, &p1, &p2, &p3, t)
+    })
+}
+
+/// Evaluate piecewise curve with `segments` pieces at `samples` uniformly spaced parameters.
+fn sample(samples: usize, segments: usize, evaluate: impl Fn(usize, f32) -> glm::Vec3) -> Box<[VertexAttribute<f32, 3>]> {
+    (0..samples)
+        .map(|sample| {
+            let parameter = sample as f32 / (samples - 1) as f32 * segments as f32;
+            // last sample belongs to the end of the last segment rather than beginning of a nonexistent one.
+            let segment = (parameter as usize).min(segments - 1);
+            let point = evaluate(segment, parameter - segment as f32);
+            VertexAttribute::from(*point.as_ref())
+        })
+        .collect::<Vec<_>>()
+        .into_boxed_slice()
+}
+
+fn catmull_rom(p0: &glm::Vec3, p1: &glm::Vec3, p2: &glm::Vec3, p3: &glm::Vec3, t: f32) -> glm::Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn cubic_bezier(p0: &glm::Vec3, p1: &glm::Vec3, p2: &glm::Vec3, p3: &glm::Vec3, t: f32) -> glm::Vec3 {
+    let s = 1.0 - t;
+    p0 * (s * s * s) + p1 * (3.0 * s * s * t) + p2 * (3.0 * s * t * t) + p3 * (t * t * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_collinear(points: &[VertexAttribute<f32, 3>], start: glm::Vec3, end: glm::Vec3) {
+        let direction = (end - start).normalize();
+        for point in points {
+            let offset = glm::make_vec3(point.as_ref()) - start;
+            assert!(offset.cross(&direction).norm() < 1e-5, "{} is off the line", offset);
+        }
+    }
+
+    #[test]
+    fn curve_through_collinear_points_stays_on_line() {
+        let control_points = [glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 0.0), glm::vec3(3.0, 3.0, 0.0), glm::vec3(4.0, 4.0, 0.0)];
+        let samples = sample_curve(&control_points, 32);
+        assert_eq!(samples.len(), 32);
+        assert_collinear(&samples, control_points[0], control_points[3]);
+    }
+
+    #[test]
+    fn curve_starts_and_ends_at_control_points() {
+        let control_points = [glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 2.0, 0.0), glm::vec3(2.0, 0.0, 1.0)];
+        let samples = sample_curve(&control_points, 9);
+        assert_eq!(glm::make_vec3(samples[0].as_ref()), control_points[0]);
+        assert!((glm::make_vec3(samples[8].as_ref()) - control_points[2]).norm() < 1e-5);
+        assert!((glm::make_vec3(samples[4].as_ref()) - control_points[1]).norm() < 1e-5);
+    }
+
+    #[test]
+    fn bezier_with_collinear_control_points_stays_on_line() {
+        let control_points = [glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 1.0), glm::vec3(0.0, 0.0, 2.0), glm::vec3(0.0, 0.0, 3.0)];
+        assert_collinear(&sample_bezier(&control_points, 16), control_points[0], control_points[3]);
+    }
+}
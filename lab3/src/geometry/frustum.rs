@@ -0,0 +1,41 @@
+use super::Aabb;
+
+use nalgebra_glm as glm;
+
+
+/// View frustum as six inward facing planes `(normal, distance)` packed into `xyzw`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum {
+    planes: [glm::Vec4; 6],
+}
+
+impl Frustum {
+    /// Extract planes from combined `projection * view` matrix (Gribb / Hartmann).
+    ///
+    /// Points inside satisfy `-w <= x, y, z <= w` in clip space so each plane is a sum or difference
+    /// of the last row with one of the remaining rows.
+    pub fn from_matrix(view_projection: &glm::Mat4) -> Self {
+        let row = |index: usize| -> glm::Vec4 { view_projection.row(index).transpose() };
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z]
+            .map(|plane| plane / plane.xyz().norm());
+        Self { planes }
+    }
+
+    pub fn planes(&self) -> &[glm::Vec4; 6] {
+        &self.planes
+    }
+
+    /// Conservative test, `false` only when the box lies entirely behind one of the planes.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // corner furthest along plane normal
+            let positive = glm::vec3(
+                if plane.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if plane.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if plane.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+            plane.xyz().dot(&positive) + plane.w >= 0.0
+        })
+    }
+}
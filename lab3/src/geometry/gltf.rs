@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use crate::uniform::Material;
+use crate::vertex::VertexAttribute;
+use crate::drawing::Winding;
+use super::{Mesh, compute_normals};
+
+use nalgebra_glm as glm;
+
+
+/// Load first mesh of `.gltf` / `.glb` file.
+///
+/// All primitives of the mesh are concatenated into single `Mesh`, indices of each primitive are offset
+/// by the number of vertices preceding it, only triangle list primitives are supported. Material of the first primitive that has one is approximated
+/// by Phong `Material` derived from its metallic roughness parameters.
+pub fn load_gltf(path: &Path) -> Result<(Mesh, Option<Material>), gltf::Error> {
+    let (document, buffers, _) = gltf::import(path)?;
+    let mesh = document.meshes().next()
+        .ok_or_else(|| gltf::Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, "document contains no meshes")))?;
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    let mut has_normals = true;
+    let mut has_uvs = true;
+    let mut material = None;
+
+    for primitive in mesh.primitives() {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            return Err(gltf::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("primitive {} uses unsupported mode {:?}, expected triangles", primitive.index(), primitive.mode()),
+            )));
+        }
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let offset = positions.len() as u32;
+
+        let primitive_positions = match reader.read_positions() {
+            Some(positions) => positions.collect::<Vec<_>>(),
+            None => continue,
+        };
+        let vertex_count = primitive_positions.len();
+        positions.extend(primitive_positions.into_iter().map(VertexAttribute::from));
+
+        match reader.read_normals() {
+            Some(primitive_normals) => normals.extend(primitive_normals.map(VertexAttribute::from)),
+            None => has_normals = false,
+        }
+        match reader.read_tex_coords(0) {
+            Some(primitive_uvs) => uvs.extend(primitive_uvs.into_f32().map(VertexAttribute::from)),
+            None => has_uvs = false,
+        }
+        match reader.read_indices() {
+            Some(primitive_indices) => indices.extend(primitive_indices.into_u32().map(|index| index + offset)),
+            None => indices.extend((0..vertex_count as u32).map(|index| index + offset)),
+        }
+
+        if material.is_none() && primitive.material().index().is_some() {
+            material = Some(phong_material(&primitive.material()));
+        }
+    }
+
+    let normals = if has_normals {
+        normals.into_boxed_slice()
+    } else {
+        compute_normals(&positions, &indices)
+    };
+    let uvs = if has_uvs && !uvs.is_empty() { Some(uvs.into_boxed_slice()) } else { None };
+
+    let mesh = Mesh {
+        positions: positions.into_boxed_slice(),
+        normals,
+        uvs,
+        indices: indices.into_boxed_slice(),
+        winding: Winding::Ccw,
+    };
+    Ok((mesh, material))
+}
+
+fn phong_material(material: &gltf::Material) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, _] = pbr.base_color_factor();
+    let base_color = glm::vec3(r, g, b);
+    let metallic = pbr.metallic_factor();
+    let roughness = pbr.roughness_factor();
+
+    // dielectrics reflect roughly 4% of light regardless of their color, metals tint reflection with base color.
+    let specular = glm::lerp(&glm::vec3(0.04, 0.04, 0.04), &base_color, metallic);
+    let diffuse = base_color * (1.0 - metallic);
+    let shininess = 2.0 + (1.0 - roughness) * 126.0;
+    Material::new(base_color * 0.1, diffuse, specular, shininess)
+}
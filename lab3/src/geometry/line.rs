@@ -1,31 +1,23 @@
 use crate::binder::Binder;
 use crate::{Direction, Directions};
 use crate::index_buffer::IndexBufferObject;
-use crate::program::Program;
+use crate::program::{Program, ProgramError};
 use crate::uniform::NamedUniform;
 
-pub fn axis(uniforms: impl Iterator<Item=NamedUniform>, direction: Direction) -> Binder<IndexBufferObject<u16>> {
+pub fn axis(uniforms: impl Iterator<Item=NamedUniform>, direction: Direction) -> Result<Binder<IndexBufferObject<u16>>, ProgramError> {
     let program = Program::from_file(
-        "shaders/line_v.glsl".as_ref(),
-        "shaders/line_f.glsl".as_ref()
-    );
-    let point = match direction {
-        Direction::Front => Directions::FRONT,
-        Direction::Back => Directions::BACK,
-        Direction::Up => Directions::UP,
-        Direction::Down => Directions::DOWN,
-        Direction::Left => Directions::LEFT,
-        Direction::Right => Directions::RIGHT,
-    };
+        "line_v.glsl".as_ref(),
+        "line_f.glsl".as_ref()
+    )?;
+    let point = Directions::world().get(&direction);
 
     let point_uniform = ("point", Box::new(point.as_ref().clone()) as _);
 
-    let mut binder  = Binder::new(
-        Vec::new(),
-        None,
+    let mut binder  = Binder::procedural(
+        2,
         program,
         uniforms.chain([point_uniform].into_iter())
     );
     binder.upload();
-    binder
+    Ok(binder)
 }
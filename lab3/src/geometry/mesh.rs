@@ -0,0 +1,57 @@
+use crate::{binder, vertex, program, index_buffer, uniform, drawing, painter};
+
+use program::Program;
+use index_buffer::IndexBufferObject;
+use vertex::{VertexAttribute, BufferObject};
+use binder::Binder;
+use uniform::NamedUniform;
+use drawing::{DrawMode, Winding};
+use painter::Painter;
+use super::Aabb;
+
+
+/// Indexed triangle mesh stored on the cpu side.
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    pub positions: Box<[VertexAttribute<f32, 3>]>,
+    pub normals: Box<[VertexAttribute<f32, 3>]>,
+    pub uvs: Option<Box<[VertexAttribute<f32, 2>]>>,
+    pub indices: Box<[u32]>,
+    /// Winding of front faces, loaders fill in the convention of the source format.
+    pub winding: Winding,
+}
+
+impl Mesh {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_positions(&self.positions)
+    }
+
+    /// Upload mesh into a binder with positions at location 0, normals at 1 and uvs (if present) at 2.
+    pub fn binder(&self, program: Program, uniforms: impl Iterator<Item=NamedUniform>) -> Binder<IndexBufferObject> {
+        let mut vbos: Vec<Box<dyn vertex::Buffer>> = vec!(
+            Box::new(BufferObject::create(self.positions.clone())),
+            Box::new(BufferObject::create(self.normals.clone())),
+        );
+        if let Some(ref uvs) = self.uvs {
+            vbos.push(Box::new(BufferObject::create(uvs.clone())));
+        }
+        let index_buf = IndexBufferObject::create(self.indices.clone());
+
+        let mut binder = Binder::new(vbos, Some(index_buf), program, uniforms);
+        binder.upload();
+        binder
+    }
+
+    /// Triangle painter over `binder` that keeps mesh's winding.
+    pub fn painter(&self, program: Program, uniforms: impl Iterator<Item=NamedUniform>) -> Painter<IndexBufferObject> {
+        Painter::new(self.binder(program, uniforms), DrawMode::Triangles).winding(self.winding)
+    }
+}
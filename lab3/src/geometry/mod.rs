@@ -1,9 +1,22 @@
-mod triangle;
-mod sphere;
+pub mod triangle;
+pub mod sphere;
 mod cube;
+pub mod cone;
+pub mod capsule;
+pub mod curve;
+pub mod tbn;
+mod mesh;
+pub mod obj;
+pub mod gltf;
+mod aabb;
+mod frustum;
 pub mod grid;
 pub mod line;
 
-pub use cube::{cube};
+pub use cube::{cube, skybox};
 pub use sphere::{sphere, sp};
-pub use triangle::{labyrinth, basic_triangle, test_triangle_model};
+pub use triangle::{labyrinth, basic_triangle, test_triangle_model, instance_offset, rotation_matrix};
+pub use tbn::compute_normals;
+pub use mesh::{Mesh};
+pub use aabb::Aabb;
+pub use frustum::Frustum;
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use crate::vertex::VertexAttribute;
+use crate::drawing::Winding;
+use super::{Mesh, compute_normals};
+
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjError::Io(error) => write!(f, "failed to read obj file: {}", error),
+            ObjError::Parse { line, message } => write!(f, "invalid obj at line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError { }
+
+impl From<std::io::Error> for ObjError {
+    fn from(error: std::io::Error) -> Self { ObjError::Io(error) }
+}
+
+/// Load Wavefront OBJ file, see `parse_obj`.
+pub fn load_obj(path: &Path) -> Result<Mesh, ObjError> {
+    let source = std::fs::read_to_string(path)?;
+    parse_obj(&source)
+}
+
+/// Parse positions, texture coordinates, normals and faces of Wavefront OBJ source.
+///
+/// Polygonal faces are triangulated as fans and identical position / uv / normal triples
+/// are merged into single vertex. Normals are computed when any face vertex lacks one.
+pub fn parse_obj(source: &str) -> Result<Mesh, ObjError> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+
+    let mut vertex_lookup = HashMap::new();
+    let mut vertices: Vec<(usize, Option<usize>, Option<usize>)> = Vec::new();
+    let mut indices = Vec::new();
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let error = |message: &str| ObjError::Parse { line: line_number, message: message.to_owned() };
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => positions.push(parse_floats::<3>(tokens, line_number)?),
+            Some("vt") => uvs.push(parse_floats::<2>(tokens, line_number)?),
+            Some("vn") => normals.push(parse_floats::<3>(tokens, line_number)?),
+            Some("f") => {
+                let mut face = Vec::new();
+                for token in tokens {
+                    let mut references = token.split('/');
+                    let position = resolve_reference(references.next(), positions.len(), line_number)?
+                        .ok_or_else(|| error("face vertex requires position index"))?;
+                    let uv = resolve_reference(references.next(), uvs.len(), line_number)?;
+                    let normal = resolve_reference(references.next(), normals.len(), line_number)?;
+
+                    let key = (position, uv, normal);
+                    let index = *vertex_lookup.entry(key).or_insert_with(|| {
+                        vertices.push(key);
+                        (vertices.len() - 1) as u32
+                    });
+                    face.push(index);
+                }
+                if face.len() < 3 {
+                    return Err(error("face requires at least 3 vertices"));
+                }
+                for corner in 1..face.len() - 1 {
+                    indices.extend([face[0], face[corner], face[corner + 1]]);
+                }
+            }
+            _ => { }
+        }
+    }
+
+    let mesh_positions = vertices.iter()
+        .map(|(position, ..)| VertexAttribute::from(positions[*position]))
+        .collect::<Vec<_>>()
+        .into_boxed_slice();
+
+    let mesh_uvs = if vertices.iter().all(|(_, uv, _)| uv.is_some()) && !vertices.is_empty() {
+        Some(vertices.iter()
+            .map(|(_, uv, _)| VertexAttribute::from(uvs[uv.unwrap()]))
+            .collect::<Vec<_>>()
+            .into_boxed_slice())
+    } else {
+        None
+    };
+
+    let mesh_normals = if vertices.iter().all(|(.., normal)| normal.is_some()) {
+        vertices.iter()
+            .map(|(.., normal)| VertexAttribute::from(normals[normal.unwrap()]))
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    } else {
+        compute_normals(&mesh_positions, &indices)
+    };
+
+    Ok(Mesh {
+        positions: mesh_positions,
+        normals: mesh_normals,
+        uvs: mesh_uvs,
+        indices: indices.into_boxed_slice(),
+        winding: Winding::Ccw,
+    })
+}
+
+fn parse_floats<'a, const N: usize>(tokens: impl Iterator<Item=&'a str>, line: usize) -> Result<[f32; N], ObjError> {
+    let mut values = [0f32; N];
+    let mut tokens = tokens;
+    for value in &mut values {
+        let token = tokens.next()
+            .ok_or_else(|| ObjError::Parse { line, message: format!("expected {} components", N) })?;
+        *value = token.parse()
+            .map_err(|_| ObjError::Parse { line, message: format!("invalid number {}", token) })?;
+    }
+    Ok(values)
+}
+
+/// Convert 1 based (or negative, relative) obj index into 0 based one, empty reference yields `None`.
+fn resolve_reference(reference: Option<&str>, count: usize, line: usize) -> Result<Option<usize>, ObjError> {
+    let reference = match reference {
+        None | Some("") => return Ok(None),
+        Some(reference) => reference,
+    };
+    let index: isize = reference.parse()
+        .map_err(|_| ObjError::Parse { line, message: format!("invalid index {}", reference) })?;
+    let resolved = if index < 0 { count as isize + index } else { index - 1 };
+    if resolved < 0 || resolved as usize >= count {
+        return Err(ObjError::Parse { line, message: format!("index {} out of range", index) });
+    }
+    Ok(Some(resolved as usize))
+}
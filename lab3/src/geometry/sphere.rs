@@ -1,10 +1,11 @@
-use crate::{binder, vertex, program, index_buffer, uniform};
+use crate::{binder, vertex, program, programs, index_buffer, uniform};
 
-use program::Program;
 use index_buffer::IndexBufferObject;
 use vertex::{VertexAttribute, BufferObject};
 use binder::Binder;
+use program::ProgramError;
 use uniform::NamedUniform;
+use super::Aabb;
 
 use nalgebra_glm as glm;
 
@@ -56,17 +57,63 @@ pub fn sp(radius: f32, poly_count: usize) -> (Box<[VertexAttribute<f32, 3>]>, Bo
     (vertices.into_boxed_slice(), normals.into_boxed_slice(), indices.into_boxed_slice())
 }
 
-pub fn sphere(uniforms: impl Iterator<Item=NamedUniform>) -> Binder<IndexBufferObject<u16>> {
+/// Sphere tessellated into near uniform triangles by subdividing an icosahedron, see `sp` for the output format.
+///
+/// Every subdivision splits each triangle into four, resulting in `10 * 4^subdivisions + 2` vertices.
+/// Midpoints shared by neighbouring triangles are created once.
+pub fn icosphere(radius: f32, subdivisions: u32) -> (Box<[VertexAttribute<f32, 3>]>, Box<[VertexAttribute<f32, 3>]>, Box<[u16]>) {
+    assert!(
+        10 * 4usize.pow(subdivisions) + 2 <= u16::MAX as usize + 1,
+        "{} subdivisions exceed 16 bit indices", subdivisions
+    );
+    // golden ratio rectangles spanning the icosahedron.
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut directions = [
+        (-1.0,  t, 0.0), ( 1.0,  t, 0.0), (-1.0, -t, 0.0), ( 1.0, -t, 0.0),
+        (0.0, -1.0,  t), (0.0,  1.0,  t), (0.0, -1.0, -t), (0.0,  1.0, -t),
+        ( t, 0.0, -1.0), ( t, 0.0,  1.0), (-t, 0.0, -1.0), (-t, 0.0,  1.0),
+    ].map(|(x, y, z)| glm::vec3(x, y, z).normalize()).to_vec();
+    let mut triangles: Vec<[u16; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    let mut midpoints = std::collections::HashMap::new();
+    for _ in 0..subdivisions {
+        let mut midpoint = |a: u16, b: u16| *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+            directions.push((directions[a as usize] + directions[b as usize]).normalize());
+            (directions.len() - 1) as u16
+        });
+        triangles = triangles.into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let vertices = directions.iter()
+        .map(|direction| VertexAttribute::from(*(direction * radius).as_ref()))
+        .collect::<Vec<_>>();
+    let normals = directions.iter()
+        .map(|direction| VertexAttribute::from(*direction.as_ref()))
+        .collect::<Vec<_>>();
+    let indices = triangles.into_iter().flatten().collect::<Vec<_>>();
+    (vertices.into_boxed_slice(), normals.into_boxed_slice(), indices.into_boxed_slice())
+}
+
+/// Unit sphere together with its bounds.
+pub fn sphere(uniforms: impl Iterator<Item=NamedUniform>) -> Result<(Binder<IndexBufferObject<u16>>, Aabb), ProgramError> {
     let (vertices, normals, indices) = sp(1.0, 25);
+    let bounds = Aabb::from_positions(&vertices).expect("sphere has vertices");
 
     let positions = Box::new(BufferObject::create(vertices));
     let normals = Box::new(BufferObject::create(normals));
     let index_buf = IndexBufferObject::create(indices);
 
-    let program = Program::from_file(
-        "shaders/sphere_v.glsl".as_ref(),
-        "shaders/sphere_f.glsl".as_ref()
-    );
+    let program = programs::sphere()?;
 
     let mut binder = Binder::new(
         vec!(positions, normals),
@@ -75,5 +122,32 @@ pub fn sphere(uniforms: impl Iterator<Item=NamedUniform>) -> Binder<IndexBufferO
         uniforms
     );
     binder.upload();
-    binder
+    Ok((binder, bounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icosphere_vertex_count_grows_with_subdivisions() {
+        for subdivisions in 0..4 {
+            let (vertices, normals, indices) = icosphere(1.0, subdivisions);
+            assert_eq!(vertices.len(), 10 * 4usize.pow(subdivisions) + 2);
+            assert_eq!(normals.len(), vertices.len());
+            assert_eq!(indices.len(), 3 * 20 * 4usize.pow(subdivisions));
+        }
+    }
+
+    #[test]
+    fn icosphere_vertices_lie_on_radius() {
+        let radius = 2.5;
+        let (vertices, normals, _) = icosphere(radius, 3);
+        for (vertex, normal) in vertices.iter().zip(normals.iter()) {
+            let vertex = glm::make_vec3(vertex.as_ref());
+            let normal = glm::make_vec3(normal.as_ref());
+            assert!((vertex.norm() - radius).abs() < 1e-5, "{} is off the sphere", vertex);
+            assert!((vertex / radius - normal).norm() < 1e-5);
+        }
+    }
 }
@@ -0,0 +1,163 @@
+use crate::vertex;
+
+use vertex::VertexAttribute;
+
+use nalgebra_glm as glm;
+
+
+fn to_vec3(attribute: &VertexAttribute<f32, 3>) -> glm::Vec3 {
+    glm::make_vec3(attribute.as_ref())
+}
+
+fn to_vec2(attribute: &VertexAttribute<f32, 2>) -> glm::Vec2 {
+    glm::make_vec2(attribute.as_ref())
+}
+
+/// Computes per vertex tangents for normal mapping.
+///
+/// Tangents are accumulated from uv deltas of every triangle sharing the vertex and then
+/// orthonormalized against vertex normal (Gram-Schmidt). Handedness of the tangent space is stored in `w`
+/// so that bitangent can be reconstructed in shader as `cross(normal, tangent.xyz) * tangent.w`.
+pub fn compute_tangents<I: Copy + Into<u32>>(
+    positions: &[VertexAttribute<f32, 3>],
+    normals: &[VertexAttribute<f32, 3>],
+    uvs: &[VertexAttribute<f32, 2>],
+    indices: &[I],
+) -> Box<[VertexAttribute<f32, 4>]> {
+    assert_eq!(positions.len(), normals.len(), "every position requires a normal");
+    assert_eq!(positions.len(), uvs.len(), "every position requires texture coordinates");
+
+    let mut tangents = vec![glm::Vec3::zeros(); positions.len()];
+    let mut bitangents = vec![glm::Vec3::zeros(); positions.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index.into() as usize);
+
+        let edge1 = to_vec3(&positions[b]) - to_vec3(&positions[a]);
+        let edge2 = to_vec3(&positions[c]) - to_vec3(&positions[a]);
+        let delta_uv1 = to_vec2(&uvs[b]) - to_vec2(&uvs[a]);
+        let delta_uv2 = to_vec2(&uvs[c]) - to_vec2(&uvs[a]);
+
+        let determinant = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+        if determinant.abs() <= f32::EPSILON {
+            // degenerate uv mapping - triangle contributes nothing.
+            continue;
+        }
+        let r = 1.0 / determinant;
+        let tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) * r;
+        let bitangent = (edge2 * delta_uv1.x - edge1 * delta_uv2.x) * r;
+
+        for index in [a, b, c] {
+            tangents[index] += tangent;
+            bitangents[index] += bitangent;
+        }
+    }
+
+    normals.iter()
+        .zip(tangents.into_iter().zip(bitangents))
+        .map(|(normal, (tangent, bitangent))| {
+            let normal = to_vec3(normal);
+            let orthogonal = tangent - normal * normal.dot(&tangent);
+            let tangent = if orthogonal.norm_squared() > f32::EPSILON {
+                orthogonal.normalize()
+            } else {
+                glm::Vec3::zeros()
+            };
+            let handedness = if normal.cross(&tangent).dot(&bitangent) < 0.0 { -1.0 } else { 1.0 };
+            VertexAttribute::from([tangent.x, tangent.y, tangent.z, handedness])
+        })
+        .collect::<Vec<_>>()
+        .into_boxed_slice()
+}
+
+/// Computes smooth per vertex normals by averaging area weighted normals of adjacent triangles.
+pub fn compute_normals<I: Copy + Into<u32>>(
+    positions: &[VertexAttribute<f32, 3>],
+    indices: &[I],
+) -> Box<[VertexAttribute<f32, 3>]> {
+    let mut normals = vec![glm::Vec3::zeros(); positions.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index.into() as usize);
+        let edge1 = to_vec3(&positions[b]) - to_vec3(&positions[a]);
+        let edge2 = to_vec3(&positions[c]) - to_vec3(&positions[a]);
+        // cross product length is proportional to triangle area which weights the contribution.
+        let face_normal = edge1.cross(&edge2);
+        for index in [a, b, c] {
+            normals[index] += face_normal;
+        }
+    }
+
+    normals.into_iter()
+        .map(|normal| {
+            let normal = if normal.norm_squared() > f32::EPSILON { normal.normalize() } else { normal };
+            VertexAttribute::from([normal.x, normal.y, normal.z])
+        })
+        .collect::<Vec<_>>()
+        .into_boxed_slice()
+}
+
+/// Duplicates vertices of every triangle and assigns them the face normal, flat shaded counterpart of `compute_normals`.
+///
+/// Returns non indexed `(positions, normals)` with three vertices per triangle, draw with `DrawMode::Triangles`.
+pub fn flat_shade<I: Copy + Into<u32>>(
+    positions: &[VertexAttribute<f32, 3>],
+    indices: &[I],
+) -> (Box<[VertexAttribute<f32, 3>]>, Box<[VertexAttribute<f32, 3>]>) {
+    let mut flat_positions = Vec::with_capacity(indices.len());
+    let mut flat_normals = Vec::with_capacity(indices.len());
+
+    for triangle in indices.chunks_exact(3) {
+        let corners = [triangle[0], triangle[1], triangle[2]].map(|index| to_vec3(&positions[index.into() as usize]));
+        let face_normal = (corners[1] - corners[0]).cross(&(corners[2] - corners[0]));
+        let face_normal = if face_normal.norm_squared() > f32::EPSILON { face_normal.normalize() } else { face_normal };
+        for corner in corners {
+            flat_positions.push(VertexAttribute::from([corner.x, corner.y, corner.z]));
+            flat_normals.push(VertexAttribute::from([face_normal.x, face_normal.y, face_normal.z]));
+        }
+    }
+    (flat_positions.into_boxed_slice(), flat_normals.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube_corners() -> Vec<VertexAttribute<f32, 3>> {
+        (0..8)
+            .map(|corner| [4, 2, 1].map(|bit| if corner & bit == 0 { -1.0 } else { 1.0 }))
+            .map(VertexAttribute::from)
+            .collect()
+    }
+
+    const CUBE_INDICES: [u8; 36] = [
+        0, 1, 5, 0, 5, 4,
+        0, 1, 3, 0, 3, 2,
+        0, 4, 6, 0, 6, 2,
+        1, 5, 7, 1, 7, 3,
+        5, 6, 4, 5, 7, 6,
+        7, 3, 2, 2, 7, 6,
+    ];
+
+    #[test]
+    fn flat_shaded_cube_has_three_vertices_per_triangle() {
+        let (positions, normals) = flat_shade(&cube_corners(), &CUBE_INDICES);
+        assert_eq!(positions.len(), 36);
+        assert_eq!(normals.len(), 36);
+    }
+
+    #[test]
+    fn flat_shaded_cube_normals_are_constant_per_face() {
+        let (positions, normals) = flat_shade(&cube_corners(), &CUBE_INDICES);
+        for (triangle, triangle_normals) in positions.chunks_exact(3).zip(normals.chunks_exact(3)) {
+            let normal = to_vec3(&triangle_normals[0]);
+            assert!(triangle_normals.iter().all(|other| to_vec3(other) == normal));
+            assert!((normal.norm() - 1.0).abs() < 1e-6);
+            assert_eq!(normal.abs().sum(), 1.0, "cube face normal {} is not axis aligned", normal);
+            // all corners of the triangle lie on the face the normal points out of.
+            let face = normal.iamax();
+            assert!(triangle.iter().all(|corner| to_vec3(corner)[face].abs() == 1.0));
+            assert!(triangle.iter().all(|corner| to_vec3(corner)[face] == to_vec3(&triangle[0])[face]));
+        }
+    }
+}
@@ -1,7 +1,6 @@
 use std::io::Read;
-use crate::{binder, vertex, program, index_buffer, uniform};
+use crate::{binder, vertex, programs, index_buffer, uniform};
 
-use program::Program;
 use index_buffer::IndexBufferObject;
 use vertex::{VertexAttribute, BufferObject};
 use binder::Binder;
@@ -21,6 +20,28 @@ pub fn template_triangle(a: f32) -> [glm::Vec3; 3] {
     ]
 }
 
+/// Center of labyrinth cell occupied by given instance, mirrors `instance_offset` in `labyrinth_v.glsl`.
+pub fn instance_offset(instance_id: u32, grid_size: f32) -> glm::Vec3 {
+    let cell_count = grid_size as u32;
+    let x = (instance_id % cell_count) as f32 / grid_size;
+    let y = ((instance_id / cell_count) % cell_count) as f32 / grid_size;
+    let z = ((instance_id / (cell_count * cell_count)) % cell_count) as f32 / grid_size;
+    let grid_center = 2.0 * glm::vec3(x, y, z) - glm::vec3(1.0, 1.0, 1.0) + glm::vec3(1.0, 1.0, 1.0) / grid_size;
+    grid_center
+}
+
+/// Rotation about arbitrary axis, mirrors `rotation_matrix` in `labyrinth_v.glsl`.
+pub fn rotation_matrix(axis: glm::Vec3, angle: f32) -> glm::Mat4 {
+    let axis = axis.normalize();
+    let s = angle.sin();
+    let c = angle.cos();
+    let oc = 1.0 - c;
+    glm::Mat4::new(oc * axis.x * axis.x + c,           oc * axis.x * axis.y - axis.z * s,  oc * axis.z * axis.x + axis.y * s,  0.0,
+                   oc * axis.x * axis.y + axis.z * s,  oc * axis.y * axis.y + c,           oc * axis.y * axis.z - axis.x * s,  0.0,
+                   oc * axis.z * axis.x - axis.y * s,  oc * axis.y * axis.z + axis.x * s,  oc * axis.z * axis.z + c,           0.0,
+                   0.0,                                0.0,                                0.0,                                1.0)
+}
+
 pub fn test_triangle_model() -> [glm::Vec3; 3] {
     [
         glm::vec3(-1.0f32, -1.0,  0.0),
@@ -32,10 +53,7 @@ pub fn test_triangle_model() -> [glm::Vec3; 3] {
 pub fn basic_triangle(uniforms: impl Iterator<Item=NamedUniform>) -> Binder<IndexBufferObject> {
     let model = test_triangle_model();
     let positions = Vec::from_iter(model.map(|val| VertexAttribute::from(val.as_ref().clone())));
-    let program = Program::from_file(
-        "shaders/triangle_v.glsl".as_ref(),
-        "shaders/triangle_f.glsl".as_ref()
-    );
+    let program = programs::triangle();
     let buffer_obj = BufferObject::create(positions.into_boxed_slice());
     let mut binder = Binder::new(
         vec!(Box::new(buffer_obj)),
@@ -47,11 +65,25 @@ pub fn basic_triangle(uniforms: impl Iterator<Item=NamedUniform>) -> Binder<Inde
     binder
 }
 
-pub fn labyrinth(uniforms: impl Iterator<Item=NamedUniform>, n: usize) -> (Binder<IndexBufferObject>, Vec<[f32; 3]>) {
+/// Instanced labyrinth triangles together with data needed to draw and collide with them.
+pub struct Labyrinth {
+    pub binder: Binder<IndexBufferObject>,
+    /// Rotation of every instance around x, y and z axis.
+    pub rotations: Vec<[f32; 3]>,
+    pub instance_count: usize,
+}
+
+/// Labyrinth of `n`^3 randomly rotated triangles, always the same one, see `labyrinth_seeded`.
+pub fn labyrinth(uniforms: impl Iterator<Item=NamedUniform>, n: usize) -> Labyrinth {
+    labyrinth_seeded(uniforms, n, 0)
+}
+
+/// Labyrinth of `n`^3 randomly rotated triangles, the same `seed` always produces the same rotations.
+pub fn labyrinth_seeded(uniforms: impl Iterator<Item=NamedUniform>, n: usize, seed: u64) -> Labyrinth {
     // let scale = 1.0 / n as f32;
     // let tail_center_offset = glm::vec3(1f32, 1f32, 1f32) / (2.0 * n as f32);
 
-    let mut rng = StdRng::seed_from_u64(0);
+    let mut rng = StdRng::seed_from_u64(seed);
     let distrib = rand::distributions::Uniform::new(0.0, std::f32::consts::PI * 2.0);
     let rotations = (0..(n * n * n))
         .into_iter()
@@ -84,21 +116,14 @@ pub fn labyrinth(uniforms: impl Iterator<Item=NamedUniform>, n: usize) -> (Binde
     // }
     let buffer_object = BufferObject::create(vertex_attrs.into_boxed_slice());
 
-    let program = Program::from_file(
-        "shaders/labyrinth_v.glsl".as_ref(),
-        "shaders/labyrinth_f.glsl".as_ref()
-    );
+    let program = programs::labyrinth();
 
-    let mut binder = Binder::new(
-        vec!(Box::new(buffer_object)),
-        None,
-        program,
-        uniforms
-    );
-    {
-        let vao_b = binder.vao_binder();
-        binder.vao().set_attrib_divisor(0, 1);
-    }
+    let mut binder = Binder::builder()
+        .add_instanced_vbo(buffer_object, 1)
+        .procedural_vertices(3)
+        .program(program)
+        .add_uniforms(uniforms)
+        .build();
     binder.upload();
-    (binder, duplicate)
+    Labyrinth { binder, instance_count: duplicate.len(), rotations: duplicate }
 }
@@ -0,0 +1,73 @@
+use std::fmt::{Display, Formatter};
+
+use gl::types::GLenum;
+
+
+/// Error flag reported by `glGetError`, see `gl_assert!` and `gl_try!`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GlError {
+    InvalidEnum,
+    InvalidValue,
+    InvalidOperation,
+    InvalidFramebufferOperation,
+    OutOfMemory,
+    StackUnderflow,
+    StackOverflow,
+    Unknown(GLenum),
+}
+
+impl GlError {
+    /// Classify value returned by `glGetError`, `None` for `GL_NO_ERROR`.
+    pub fn from_code(code: GLenum) -> Option<Self> {
+        match code {
+            gl::NO_ERROR => None,
+            gl::INVALID_ENUM => Some(GlError::InvalidEnum),
+            gl::INVALID_VALUE => Some(GlError::InvalidValue),
+            gl::INVALID_OPERATION => Some(GlError::InvalidOperation),
+            gl::INVALID_FRAMEBUFFER_OPERATION => Some(GlError::InvalidFramebufferOperation),
+            gl::OUT_OF_MEMORY => Some(GlError::OutOfMemory),
+            gl::STACK_UNDERFLOW => Some(GlError::StackUnderflow),
+            gl::STACK_OVERFLOW => Some(GlError::StackOverflow),
+            code => Some(GlError::Unknown(code)),
+        }
+    }
+
+    /// Pop oldest error flag of current context.
+    pub fn check() -> Result<(), Self> {
+        match Self::from_code(unsafe { gl::GetError() }) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Get opengl defined numerical value of given variant
+    pub fn get(&self) -> GLenum {
+        match *self {
+            GlError::InvalidEnum => gl::INVALID_ENUM,
+            GlError::InvalidValue => gl::INVALID_VALUE,
+            GlError::InvalidOperation => gl::INVALID_OPERATION,
+            GlError::InvalidFramebufferOperation => gl::INVALID_FRAMEBUFFER_OPERATION,
+            GlError::OutOfMemory => gl::OUT_OF_MEMORY,
+            GlError::StackUnderflow => gl::STACK_UNDERFLOW,
+            GlError::StackOverflow => gl::STACK_OVERFLOW,
+            GlError::Unknown(code) => code,
+        }
+    }
+}
+
+impl Display for GlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlError::InvalidEnum => write!(f, "GL_INVALID_ENUM"),
+            GlError::InvalidValue => write!(f, "GL_INVALID_VALUE"),
+            GlError::InvalidOperation => write!(f, "GL_INVALID_OPERATION"),
+            GlError::InvalidFramebufferOperation => write!(f, "GL_INVALID_FRAMEBUFFER_OPERATION"),
+            GlError::OutOfMemory => write!(f, "GL_OUT_OF_MEMORY"),
+            GlError::StackUnderflow => write!(f, "GL_STACK_UNDERFLOW"),
+            GlError::StackOverflow => write!(f, "GL_STACK_OVERFLOW"),
+            GlError::Unknown(code) => write!(f, "unknown error {:#x}", code),
+        }
+    }
+}
+
+impl std::error::Error for GlError { }
@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+use gl::types::GLuint;
+
+use crate::gl_assert_no_err;
+
+
+/// `GL_TIME_ELAPSED` query around a block of gl commands, result becomes available asynchronously.
+pub struct QueryHandle {
+    id: GLuint,
+}
+
+impl QueryHandle {
+    /// Measure gpu time spent executing commands issued by `commands`.
+    ///
+    /// Time elapsed queries can not nest, `commands` must not start another one.
+    pub fn measure(commands: impl FnOnce()) -> Self {
+        let mut id = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateQueries(gl::TIME_ELAPSED, 1, &mut id);
+            gl::BeginQuery(gl::TIME_ELAPSED, id);
+        }
+        gl_assert_no_err!();
+        commands();
+        gl_assert_no_err!();
+        unsafe { gl::EndQuery(gl::TIME_ELAPSED); }
+        gl_assert_no_err!();
+        Self { id }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        let mut available = 0;
+        gl_assert_no_err!();
+        unsafe { gl::GetQueryObjectiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available); }
+        gl_assert_no_err!();
+        available != 0
+    }
+
+    /// Elapsed gpu time in nanoseconds, `None` while gpu has not finished the commands yet.
+    ///
+    /// Poll the frame after issuing the query to avoid stalling the pipeline.
+    pub fn poll(&self) -> Option<u64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let mut elapsed = 0;
+        gl_assert_no_err!();
+        unsafe { gl::GetQueryObjectui64v(self.id, gl::QUERY_RESULT, &mut elapsed); }
+        gl_assert_no_err!();
+        Some(elapsed)
+    }
+}
+
+impl Drop for QueryHandle {
+    fn drop(&mut self) {
+        log::debug!("Deleting query {}", self.id);
+        unsafe { gl::DeleteQueries(1, &self.id); }
+    }
+}
+
+/// Keeps queries in flight across frames and reports the most recent finished measurement.
+#[derive(Default)]
+pub struct GpuTimer {
+    pending: VecDeque<QueryHandle>,
+    last_elapsed: Option<u64>,
+}
+
+impl GpuTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue new query around `commands`, its result is picked up by later `poll`.
+    pub fn measure(&mut self, commands: impl FnOnce()) {
+        self.pending.push_back(QueryHandle::measure(commands));
+    }
+
+    /// Collect finished queries in issue order, returns nanoseconds of the latest finished one.
+    pub fn poll(&mut self) -> Option<u64> {
+        while let Some(elapsed) = self.pending.front().and_then(QueryHandle::poll) {
+            self.last_elapsed = Some(elapsed);
+            self.pending.pop_front();
+        }
+        self.last_elapsed
+    }
+}
@@ -16,10 +16,6 @@ pub trait IndexBuffer {
     fn vertex_count(&self) -> usize;
 
     fn index_type(&self) -> IndexType;
-
-    fn scoped_binder(&self) -> ScopedBinder {
-        ScopedBinder::new(self.id())
-    }
 }
 
 impl<B: IndexBuffer> GlBufferTargetProvider for B {
@@ -53,6 +49,15 @@ impl IndexType {
         }
     }
 
+    /// Size of single index in bytes.
+    pub fn size_bytes(&self) -> usize {
+        match *self {
+            Self::U8 => 1,
+            Self::U16 => 2,
+            Self::U32 => 4,
+        }
+    }
+
     pub fn from_type<IP: IndexingPrimitive>() -> Self {
         IP::INDEX_TYPE
     }
@@ -69,11 +74,11 @@ impl<P: IndexingPrimitive> IndexBuffer for IndexBufferObject<P> {
     fn upload(&self) {
         gl_assert_no_err!();
         unsafe {
-            gl::BufferData(
-                <Self as GlBufferTargetProvider>::TARGET,
+            gl::NamedBufferData(
+                self.id,
                 (self.vertex_count() * std::mem::size_of::<P>()) as _,
                 self.indices.as_ptr() as *const std::ffi::c_void,
-            gl::STATIC_DRAW
+                gl::STATIC_DRAW
             );
         }
         gl_assert_no_err!();
@@ -102,36 +107,33 @@ impl<P: IndexingPrimitive> IndexBufferObject<P> {
         gl_assert_no_err!();
         Self { id, indices }
     }
-}
-
-impl<P: IndexingPrimitive> Drop for IndexBufferObject<P> {
-    fn drop(&mut self) {
-        gl_assert_no_err!();
-        unsafe { gl::DeleteBuffers(1, &self.id); }
-        gl_assert_no_err!();
-    }
-}
-
-
-pub struct ScopedBinder(GLuint);
 
-impl ScopedBinder {
-    pub fn new(id : GLuint) -> Self {
-        gl_assert_no_err!();
-        unsafe {
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, id);
-        }
-        gl_assert_no_err!();
-        Self(id)
+    /// Collect indices produced lazily, e.g. by strip or grid generators, into index buffer of type `P`.
+    ///
+    /// Panics if an index does not fit `P`, debug builds also check that indices address one of `vertex_count` vertices.
+    pub fn from_iter(indices: impl IntoIterator<Item=usize>, vertex_count: usize) -> Self
+        where
+            P: TryFrom<usize>
+    {
+        let indices = indices.into_iter()
+            .map(|index| {
+                debug_assert!(index < vertex_count, "index {} out of range for {} vertices", index, vertex_count);
+                P::try_from(index).ok()
+                    .unwrap_or_else(|| panic!("index {} is not representable as {:?}", index, P::INDEX_TYPE))
+            })
+            .collect::<Vec<_>>();
+        Self::create(indices.into_boxed_slice())
     }
 }
 
-impl Drop for ScopedBinder {
+impl<P: IndexingPrimitive> Drop for IndexBufferObject<P> {
     fn drop(&mut self) {
-        gl_assert_no_err!();
-        unsafe {
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.0);
+        if self.id == 0 {
+            return;
         }
+        log::debug!("Deleting index buffer object {}", self.id);
+        gl_assert_no_err!();
+        unsafe { gl::DeleteBuffers(1, &self.id); }
         gl_assert_no_err!();
     }
 }
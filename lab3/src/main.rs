@@ -1,6 +1,8 @@
 mod vertex;
 mod geometry;
 mod program;
+mod preprocessor;
+mod program_cache;
 mod uniform;
 mod camera;
 mod index_buffer;
@@ -8,33 +10,68 @@ mod drawing;
 mod binder;
 mod painter;
 mod colliders;
+mod scene_graph;
+mod texture;
+mod texture_loader;
+mod text;
+mod skybox;
+mod state_cache;
+mod screenshot;
+mod framebuffer;
+mod picking;
+mod stencil;
+mod render_state;
+mod viewport;
+mod frame_timer;
+mod render_loop;
+mod debug;
+mod controls;
+mod consts;
+mod context;
+mod gl_error;
+mod storage_buffer;
+mod transform_feedback;
+mod gpu_timer;
+mod programs;
+mod shader_loader;
+mod shadow_map;
+mod deferred;
+mod scene;
 
 use glutin;
 use gl;
 use nalgebra_glm as glm;
 
-use std::io::Write;
 use std::default::Default;
-use std::time::{Duration, Instant};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::Instant;
 
 use drawing::DrawMode;
 use camera::Camera;
 use painter::Painter;
-
-use glutin::event::{DeviceEvent, ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use stencil::StencilState;
+use viewport::Viewport;
+use frame_timer::FrameTimer;
+use render_loop::{RenderLoop, RedrawMode};
+use controls::Controls;
+use consts::{SRGB_OUTPUT, CAMERA_POSE_PATH};
+use context::{GlContext, ContextConfig};
+use scene::Scene;
+use shadow_map::ShadowMap;
+
+use glutin::event::{DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
 use glutin::event_loop::{EventLoop, ControlFlow};
 use glutin::window::{WindowBuilder};
-use glutin::{Api, GlRequest};
 use glutin::dpi::PhysicalPosition;
-use nalgebra_glm::Mat4;
-use crate::camera::{CameraPerspectiveState, CameraProvider, CameraViewState, FixedMovable, FreeRoamingCamera, KinematicCamera, PerspectiveMatrixProvider, ViewMatrixProvider};
+use crate::camera::{Projection, CameraViewState, FixedMovable, FreeRoamingCamera, KinematicCamera, PerspectiveMatrixProvider, ViewMatrixProvider};
 use crate::colliders::capsule::{Capsule, Collider};
+use crate::uniform::{F32Uniform, Vec3Uniform, Mat4Uniform, Light, RotatingLight};
 
 // todo: Objects can emit painters which borrow data from them during upload.
 //  data must be interpretable as &[VertexAttribute], &[IndexingPrimitive] and perhaps uniforms and programs.
 //  assert that all buffer objects contain the same number of atts? what about indexing.
 //  move instance_count information to buffer
-const GL_VERSION: (u8, u8) = (4, 5);
 
 #[macro_export]
 macro_rules! gl_assert_no_err {
@@ -48,27 +85,24 @@ macro_rules! gl_assert {
     ($s:stmt) => {
         $s
         if cfg!(debug_assertions) {
-            let err = gl::GetError();
-            match err {
-                gl::NO_ERROR => {
-                },
-               _ => {
-                    match err {
-                        gl::INVALID_ENUM => panic!("GL_INVALID_ENUM"),
-                        gl::INVALID_VALUE => panic!("GL_INVALID_VALUE"),
-                        gl::INVALID_OPERATION => panic!("GL_INVALID_OPERATION"),
-                        gl::INVALID_FRAMEBUFFER_OPERATION => panic!("GL_INVALID_FRAMEBUFFER_OPERATION"),
-                        gl::OUT_OF_MEMORY => panic!("GL_OUT_OF_MEMORY"),
-                        gl::STACK_UNDERFLOW => panic!("GL_STACK_UNDERFLOW"),
-                        gl::STACK_OVERFLOW => panic!("GL_STACK_OVERFLOW"),
-                        _ => panic!("unknown error")
-                    }
-                }
+            if let Err(error) = $crate::gl_error::GlError::check() {
+                panic!("{}", error);
             }
         };
     }
 }
 
+/// Like `gl_assert!` but returns `Err(GlError)` from enclosing function, errors are checked in release builds too.
+#[macro_export]
+macro_rules! gl_try {
+    ($s:stmt) => {
+        $s
+        if let Err(error) = $crate::gl_error::GlError::check() {
+            return Err(error.into());
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct CoordinateSystem {
     center: glm::Vec3,
@@ -79,17 +113,49 @@ pub struct CoordinateSystem {
 
 impl CoordinateSystem {
     const CENTER: glm::Vec3 = glm::Vec3::new(0f32, 0f32, 0f32);
-}
+    const X_COLOR: [f32; 3] = [1.0, 0.0, 0.0];
+    const Y_COLOR: [f32; 3] = [0.0, 1.0, 0.0];
+    const Z_COLOR: [f32; 3] = [0.0, 0.0, 1.0];
 
-#[derive(Debug, Copy, Clone)]
-pub struct Scene {
-    bg_color: glm::Vec4,
-    // skybox: geometry::Cube
-}
+    pub fn new(center: glm::Vec3, x: glm::Vec4, y: glm::Vec4, z: glm::Vec4) -> Self {
+        Self { center, x, y, z }
+    }
+
+    /// Axes of world space placed at the origin.
+    pub fn world() -> Self {
+        Self::new(
+            Self::CENTER,
+            glm::vec4(1.0, 0.0, 0.0, 0.0),
+            glm::vec4(0.0, 1.0, 0.0, 0.0),
+            glm::vec4(0.0, 0.0, 1.0, 0.0),
+        )
+    }
 
-impl Scene {
-    const DARK_GRAY:  glm::Vec3 = glm::Vec3::new(0.23, 0.23, 0.23);
-    const LIGHT_BLUE: glm::Vec3 = glm::Vec3::new(0.54, 0.82, 1.0);
+    /// Line segment from center along every axis, two vertices per axis with matching colors.
+    pub fn axis_lines(&self) -> (Box<[vertex::VertexAttribute<f32, 3>]>, Box<[vertex::VertexAttribute<f32, 3>]>) {
+        let center = *self.center.as_ref();
+        let mut positions = Vec::with_capacity(6);
+        let mut colors = Vec::with_capacity(6);
+        for (axis, color) in [(self.x, Self::X_COLOR), (self.y, Self::Y_COLOR), (self.z, Self::Z_COLOR)] {
+            let end = self.center + axis.xyz();
+            positions.extend([vertex::VertexAttribute::from(center), vertex::VertexAttribute::from(*end.as_ref())]);
+            colors.extend([vertex::VertexAttribute::from(color), vertex::VertexAttribute::from(color)]);
+        }
+        (positions.into_boxed_slice(), colors.into_boxed_slice())
+    }
+
+    /// Gizmo with red X, green Y and blue Z axis, uniforms must provide camera matrices.
+    pub fn painter(&self, uniforms: impl Iterator<Item=uniform::NamedUniform>) -> Painter<index_buffer::IndexBufferObject<u8>> {
+        let (positions, colors) = self.axis_lines();
+        let mut binder = binder::Binder::builder()
+            .add_named_vbo("position", vertex::BufferObject::create(positions))
+            .add_named_vbo("color", vertex::BufferObject::create(colors))
+            .program(programs::vertex_color())
+            .add_uniforms(uniforms)
+            .build();
+        binder.upload();
+        Painter::new(binder, DrawMode::Lines)
+    }
 }
 
 pub enum Direction {
@@ -118,6 +184,42 @@ impl Directions {
     const DOWN:  glm::Vec3 = glm::Vec3::new( 0f32, -1f32,  0f32);
     const RIGHT: glm::Vec3 = glm::Vec3::new( 1f32,  0f32,  0f32);
     const LEFT:  glm::Vec3 = glm::Vec3::new(-1f32,  0f32,  0f32);
+
+    /// World space directions.
+    pub fn world() -> Self {
+        Self {
+            up: Self::UP,
+            down: Self::DOWN,
+            front: Self::FRONT,
+            back: Self::BACK,
+            left: Self::LEFT,
+            right: Self::RIGHT,
+        }
+    }
+
+    /// Directions relative to where the camera is looking, used for fly camera movement.
+    pub fn from_orientation(camera: &Camera) -> Self {
+        let orientation = camera.orientation();
+        Self {
+            up: orientation.direction(&Direction::Up),
+            down: orientation.direction(&Direction::Down),
+            front: orientation.direction(&Direction::Front),
+            back: orientation.direction(&Direction::Back),
+            left: orientation.direction(&Direction::Left),
+            right: orientation.direction(&Direction::Right),
+        }
+    }
+
+    pub fn get(&self, direction: &Direction) -> glm::Vec3 {
+        match direction {
+            Direction::Front => self.front,
+            Direction::Back => self.back,
+            Direction::Up => self.up,
+            Direction::Down => self.down,
+            Direction::Left => self.left,
+            Direction::Right => self.right,
+        }
+    }
 }
 
 fn screen_center(window: &glutin::window::Window) -> PhysicalPosition<u32>{
@@ -130,30 +232,14 @@ fn center_cursor(window: &glutin::window::Window) {
     window.set_cursor_position(center).unwrap();
 }
 
-fn temp_instance_offset(instance_id: u32, grid_size: f32) -> glm::Vec3 {
-    let cell_count = grid_size as u32;
-    let x = (instance_id % cell_count) as f32 / grid_size;
-    let y = ((instance_id / cell_count) % cell_count) as f32 / grid_size;
-    let z = ((instance_id / (cell_count * cell_count)) % cell_count) as f32 / grid_size;
-    let grid_center = 2.0 * glm::vec3(x, y, z) - glm::vec3(1.0, 1.0, 1.0) + glm::vec3(1.0, 1.0, 1.0) / grid_size;
-    grid_center
-}
-
-fn rotation_matrix(axis: glm::Vec3, angle: f32) -> Mat4 {
-    let axis = axis.normalize();
-    let s = angle.sin();
-    let c = angle.cos();
-    let oc = 1.0 - c;
-    Mat4::new(oc * axis.x * axis.x + c,           oc * axis.x * axis.y - axis.z * s,  oc * axis.z * axis.x + axis.y * s,  0.0,
-                oc * axis.x * axis.y + axis.z * s,  oc * axis.y * axis.y + c,           oc * axis.y * axis.z - axis.x * s,  0.0,
-                oc * axis.z * axis.x - axis.y * s,  oc * axis.y * axis.z + axis.x * s,  oc * axis.z * axis.z + c,           0.0,
-                0.0,                                0.0,                                0.0,                                1.0)
-}
-
 // object is drawable
 // there's no easy way to know if transforms should be updated.
+/// Object safe so that painters with different index types can share one render list.
 pub trait Drawable {
-    fn draw<C: CameraProvider>(&mut self, camera: &C);
+    fn draw(&self);
+
+    /// Point drawable at given camera, drawables that do not depend on camera ignore it.
+    fn set_camera(&mut self, _view: &glm::Mat4, _projection: &glm::Mat4) { }
 }
 
 // object must be places in world
@@ -167,6 +253,14 @@ impl Transform {
     pub fn new(position: glm::Vec3, rotation: glm::Vec3, scale: glm::Vec3) -> Self {
         Self { position, rotation, scale }
     }
+
+    /// Model matrix scaling first, then rotating by euler angles in radians about x, y and z, then translating.
+    pub fn matrix(&self) -> glm::Mat4 {
+        let rotation = glm::rotation(self.rotation.z, &glm::Vec3::z())
+            * glm::rotation(self.rotation.y, &glm::Vec3::y())
+            * glm::rotation(self.rotation.x, &glm::Vec3::x());
+        glm::translation(&self.position) * rotation * glm::scaling(&self.scale)
+    }
 }
 
 impl Default for Transform {
@@ -219,23 +313,19 @@ pub trait GameObject: Drawable + ComponentProvider<Transform> { }
 // }
 
 
+/// Report error on stderr and exit, for failures the demo can not recover from such as missing shaders.
+fn or_exit<T, E: std::fmt::Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1)
+    })
+}
+
 fn main() {
     let event_loop = EventLoop::new();
-    let window = WindowBuilder::new().with_title("3D labyrinth");
-
-    let gl_context = glutin::ContextBuilder::new()
-        .with_gl(GlRequest::Specific(Api::OpenGl, GL_VERSION))
-        // .with_vsync(true)
-        .build_windowed(window, &event_loop)
-        .expect("Cannot create windowed context");
-
-    let gl_context = unsafe {
-        gl_context
-            .make_current()
-            .expect("Failed to make context current")
-    };
+    let window = WindowBuilder::new().with_title(consts::WINDOW_TITLE);
 
-    gl::load_with(|ptr| gl_context.get_proc_address(ptr) as *const _);
+    let gl_context = or_exit(GlContext::new(&event_loop, window, &ContextConfig::default()));
 
     gl_context.window().set_cursor_visible(false);
 
@@ -246,12 +336,12 @@ fn main() {
 
     // todo: add grid near center and also lines for axis reference.
 
-    let perspective = CameraPerspectiveState::default();
+    let perspective = Projection::default();
     let view = CameraViewState::default();
     let mut free_roam_cam = FreeRoamingCamera::from(Camera::new(perspective.clone(), view));
     let mut hero_cam = camera::HeroShotCamera::new(perspective, Directions::FRONT, CoordinateSystem::CENTER, 4.0);
 
-    let labyrinth_grid_size = 6;
+    let labyrinth_grid_size = consts::LABYRINTH_GRID_SIZE;
     let light_direction = Directions::DOWN + Directions::RIGHT + Directions::FRONT;
 
     let mut triangle_color = glm::vec3(0.0, 1.0, 0.0);
@@ -263,12 +353,13 @@ fn main() {
     const GRID_SIZE_ID: &str = "grid_size";
     const POINT_ID: &str = "point";
     const COLOR_ID: &str = "color";
+    const LIGHT_SPACE_MATRIX_ID: &str = "light_space_matrix";
 
     let mut test_triangle_uniforms = uniform::to_owned([
         (PERSPECTIVE_MATRIX_ID, free_roam_cam.perspective_matrix()),
         (VIEW_MATRIX_ID, free_roam_cam.view_matrix()),
     ]).collect::<Vec<_>>();
-    test_triangle_uniforms.push((COLOR_ID, Box::new(triangle_color.as_ref().clone())));
+    test_triangle_uniforms.push((COLOR_ID, Box::new(Vec3Uniform(triangle_color))));
     let mut labyrinth_uniforms = uniform::to_owned([
         (PERSPECTIVE_MATRIX_ID, free_roam_cam.perspective_matrix()),
         (VIEW_MATRIX_ID, free_roam_cam.view_matrix()),
@@ -281,16 +372,30 @@ fn main() {
         (PERSPECTIVE_MATRIX_ID, free_roam_cam.perspective_matrix()),
         (VIEW_MATRIX_ID, free_roam_cam.view_matrix()),
     ]).collect::<Vec<_>>();
-    sphere_uniforms.push((LIGHT_DIRECTION_ID, Box::new(light_direction.as_ref().clone())));
-    sphere_uniforms.push((PLAYER_POSITION_ID, Box::new(free_roam_cam.get_position().as_ref().clone())));
-    labyrinth_uniforms.push((GRID_SIZE_ID, Box::new(labyrinth_grid_size as f32) as _));
-
-    let mut test_triangle = Painter::new(geometry::basic_triangle(test_triangle_uniforms.into_iter()), DrawMode::Triangles);
+    let sun = Light::directional(light_direction, glm::vec3(1.0, 1.0, 1.0), 1.0);
+    let sun = RotatingLight::new(sun, Directions::UP, consts::SUN_ANGULAR_SPEED);
+    sphere_uniforms.push((LIGHT_DIRECTION_ID, Box::new(sun)));
+    sphere_uniforms.push((PLAYER_POSITION_ID, Box::new(Vec3Uniform(free_roam_cam.get_position()))));
+    sphere_uniforms.push((LIGHT_SPACE_MATRIX_ID, Box::new(Mat4Uniform(glm::Mat4::identity()))));
+    labyrinth_uniforms.push((GRID_SIZE_ID, Box::new(F32Uniform(labyrinth_grid_size as f32)) as _));
+    labyrinth_uniforms.push((LIGHT_SPACE_MATRIX_ID, Box::new(Mat4Uniform(glm::Mat4::identity()))));
+    labyrinth_uniforms.push((LIGHT_DIRECTION_ID, Box::new(sun)));
+
+    const TRIANGLE_PICKING_ID: u32 = 1;
+    const SPHERE_PICKING_ID: u32 = 2;
+
+    let test_triangle = Rc::new(RefCell::new(
+        Painter::new(geometry::basic_triangle(test_triangle_uniforms.into_iter()), DrawMode::Triangles)
+            .pickable(TRIANGLE_PICKING_ID, or_exit(program::Program::from_file(
+                "triangle_v.glsl".as_ref(),
+                "picking_f.glsl".as_ref(),
+            )))
+    ));
     let test_triangle_model = geometry::test_triangle_model();
 
-    let (lab_binder, rotations) = geometry::labyrinth(labyrinth_uniforms.into_iter(), labyrinth_grid_size);
+    let labyrinth = geometry::labyrinth(labyrinth_uniforms.into_iter(), labyrinth_grid_size);
 
-    let mut axis_painters = {
+    let axis_painters = {
         let mut x_axis = uniform::to_owned([
             (PERSPECTIVE_MATRIX_ID, free_roam_cam.perspective_matrix()),
             (VIEW_MATRIX_ID, free_roam_cam.view_matrix()),
@@ -311,15 +416,15 @@ fn main() {
 
         [
             Painter::new(
-            geometry::line::axis(x_axis.into_iter(), Direction::Right),
+            or_exit(geometry::line::axis(x_axis.into_iter(), Direction::Right)),
             DrawMode::Lines
             ),
             Painter::new(
-            geometry::line::axis(y_axis.into_iter(), Direction::Up),
+            or_exit(geometry::line::axis(y_axis.into_iter(), Direction::Up)),
             DrawMode::Lines
             ),
             Painter::new(
-            geometry::line::axis(z_axis.into_iter(), Direction::Back),
+            or_exit(geometry::line::axis(z_axis.into_iter(), Direction::Back)),
             DrawMode::Lines
             ),
         ]
@@ -331,16 +436,15 @@ fn main() {
         glm::vec3(0.0, 0.0, 1.0)
     ];
 
-    let colliders: Vec<_> = (0..labyrinth_grid_size * labyrinth_grid_size * labyrinth_grid_size)
-        .into_iter()
-        .zip(rotations)
+    let colliders: Vec<_> = (0..labyrinth.instance_count)
+        .zip(labyrinth.rotations)
         .map(|(index, rotation_vec)| {
             let mut triangle = test_triangle_model.clone();
-            let offset = temp_instance_offset(index as _, labyrinth_grid_size as _);
+            let offset = geometry::instance_offset(index as _, labyrinth_grid_size as _);
             for t in &mut triangle {
                 for (axis_index, rotation) in rotation_vec.iter().enumerate() {
                     let extended = glm::Vec4::new(t.x, t.y, t.z, 1.0);
-                    *t = dbg!((rotation_matrix(axis[axis_index], *rotation) * extended).xyz());
+                    *t = (geometry::rotation_matrix(axis[axis_index], *rotation) * extended).xyz();
                 }
                 *t = *t / (labyrinth_grid_size as f32 * 2f32) + offset;
             }
@@ -352,10 +456,32 @@ fn main() {
         }
     ).collect();
 
-    let mut labyrinth_painter = Painter::new(lab_binder, DrawMode::Triangles)
-        .instanced(labyrinth_grid_size * labyrinth_grid_size * labyrinth_grid_size);
-    let mut skybox_painter = Painter::new(geometry::cube(skybox_uniforms), DrawMode::Triangles);
-    let mut sphere_painter = Painter::new(geometry::sphere(sphere_uniforms.into_iter()), DrawMode::Triangles);
+    let labyrinth_painter = Rc::new(RefCell::new(
+        Painter::new(labyrinth.binder, DrawMode::Triangles)
+            .instanced(labyrinth.instance_count)
+            .double_sided(true)
+            .shadow_caster(programs::labyrinth_shadow())
+    ));
+    let (skybox_binder, _) = or_exit(geometry::cube(skybox_uniforms));
+    let (sphere_binder, _) = or_exit(geometry::sphere(sphere_uniforms.into_iter()));
+    let skybox_painter = Painter::new(skybox_binder, DrawMode::Triangles);
+    let sphere_painter = Rc::new(RefCell::new(
+        Painter::new(sphere_binder, DrawMode::Triangles)
+            .pickable(SPHERE_PICKING_ID, or_exit(program::Program::from_file(
+                "sphere_v.glsl".as_ref(),
+                "picking_f.glsl".as_ref(),
+            )))
+            .shadow_caster(programs::sphere_shadow())
+            .stencil(StencilState::write(1))
+    ));
+    let sphere_outline = programs::sphere_outline();
+    let mut sphere_selected = false;
+    let mut shadow_map = ShadowMap::new(ShadowMap::DEFAULT_SIZE);
+
+    let window_size = gl_context.window().inner_size();
+    let mut picker = picking::Picker::new(window_size.width, window_size.height);
+    let mut cursor_position = PhysicalPosition::new(0.0, 0.0);
+    let mut pick_requested = false;
 
     gl_assert_no_err!();
     unsafe {
@@ -364,16 +490,104 @@ fn main() {
     }
     gl_assert_no_err!();
 
-    let mut frame_rate_display = Instant::now();
+    framebuffer::set_srgb_conversion(SRGB_OUTPUT);
+    let bg_color = glm::vec3(Scene::LIGHT_BLUE.x, Scene::DARK_GRAY.y, Scene::LIGHT_BLUE.z);
+    let bg_color = if SRGB_OUTPUT { bg_color.map(framebuffer::srgb_to_linear) } else { bg_color };
+    let mut scene = Scene::new(
+        glm::vec4(bg_color.x, bg_color.y, bg_color.z, 1.0),
+        free_roam_cam.view_matrix(),
+        free_roam_cam.perspective_matrix(),
+    );
+    scene.add(Rc::clone(&test_triangle));
+    scene.add(Rc::clone(&labyrinth_painter));
+    let sphere_id = scene.add(Rc::clone(&sphere_painter));
+    for painter in axis_painters {
+        scene.add(painter);
+    }
+    scene.add(skybox_painter);
+
+    let mut frame_timer = FrameTimer::new().print_in_place(true);
     let mut draw_mode = DrawMode::Triangles;
 
     const FREE_ROAM_CAM: usize = 0;
     const HERO_CAM: usize = 1;
     let mut current_cam = FREE_ROAM_CAM;
 
-    let mut fps_counter = 0;
+    let mut take_screenshot = false;
+    let mut split_screen = false;
+    let mut controls = Controls::default();
+    let mut render_loop = RenderLoop::new(RedrawMode::OnEvent);
     event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Wait;
+        render_loop.handle(&event, gl_context.window(), control_flow, |_| (), || {
+            labyrinth_painter.borrow_mut().update_draw_mode(draw_mode);
+            sphere_painter.borrow_mut().update_draw_mode(draw_mode);
+            scene.set_visible(sphere_id, current_cam != FREE_ROAM_CAM);
+
+            // sphere and labyrinth shaders light normals facing `light_direction`, so the light travels the opposite way.
+            shadow_map.set_light(&-sun.direction_at(Instant::now()), &glm::Vec3::zeros(), consts::SHADOW_RADIUS);
+            let light_space_matrix = shadow_map.light_space_matrix();
+            labyrinth_painter.borrow_mut().binder_mut()
+                .update_uniform(LIGHT_SPACE_MATRIX_ID, Box::new(Mat4Uniform(light_space_matrix)));
+            sphere_painter.borrow_mut().binder_mut()
+                .update_uniform(LIGHT_SPACE_MATRIX_ID, Box::new(Mat4Uniform(light_space_matrix)));
+            shadow_map.render(|light_view, light_projection| {
+                labyrinth_painter.borrow().draw_shadow(light_view, light_projection);
+                if scene.is_visible(sphere_id) {
+                    sphere_painter.borrow().draw_shadow(light_view, light_projection);
+                }
+            });
+            shadow_map.bind_texture(ShadowMap::TEXTURE_UNIT);
+
+            let split_cameras = [
+                (free_roam_cam.view_matrix(), *free_roam_cam.projection()),
+                (hero_cam.view_matrix(), *hero_cam.projection()),
+            ];
+            if split_screen {
+                scene.clear();
+                let (left, right) = Viewport::current().split_horizontally();
+                viewport::render_split(&[left, right], |index, viewport| {
+                    let (view, mut projection) = split_cameras[index];
+                    projection.set_aspect_ratio(viewport.aspect_ratio());
+                    scene.set_camera(&view, &projection.matrix());
+                    scene.draw();
+                });
+            }
+            // full window view of current camera is also used for picking and next frame.
+            let (view, projection) = split_cameras[current_cam];
+            scene.set_camera(&view, &projection.matrix());
+            if !split_screen {
+                scene.render();
+                if sphere_selected && scene.is_visible(sphere_id) {
+                    sphere_painter.borrow().draw_outline(&sphere_outline);
+                }
+            }
+            if pick_requested {
+                pick_requested = false;
+                picker.render(|| {
+                    test_triangle.borrow().draw_picking();
+                    if scene.is_visible(sphere_id) {
+                        sphere_painter.borrow().draw_picking();
+                    }
+                });
+                let picked = picker.read(cursor_position);
+                sphere_selected = picked == Some(SPHERE_PICKING_ID);
+                match picked {
+                    Some(TRIANGLE_PICKING_ID) => println!("\nPicked triangle"),
+                    Some(SPHERE_PICKING_ID) => println!("\nPicked sphere"),
+                    Some(id) => println!("\nPicked object {}", id),
+                    None => println!("\nNothing picked"),
+                }
+            }
+            if take_screenshot {
+                take_screenshot = false;
+                match screenshot::save_screenshot(consts::SCREENSHOT_PATH.as_ref()) {
+                    Ok(()) => println!("\nScreenshot saved to {}", consts::SCREENSHOT_PATH),
+                    Err(error) => eprintln!("\nFailed to save screenshot: {}", error),
+                }
+            }
+            gl_context.swap_buffers().unwrap();
+            frame_timer.frame();
+        });
         // todo: for smoother movement and better frame rates process all inputs once per each frame.
 
         match event {
@@ -381,6 +595,26 @@ fn main() {
             Event::WindowEvent { event, .. } => {
                 match event {
                     WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                    WindowEvent::Resized(size) => {
+                        gl_context.resize(size);
+                        gl_assert_no_err!();
+                        unsafe { gl::Viewport(0, 0, size.width as _, size.height as _); }
+                        gl_assert_no_err!();
+                        picker.resize(size.width, size.height);
+                    },
+                    WindowEvent::CursorMoved { position, .. } => cursor_position = position,
+                    WindowEvent::ModifiersChanged(modifiers) => controls.set_boosted(modifiers.shift()),
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        let lines = match delta {
+                            MouseScrollDelta::LineDelta(_, y) => y,
+                            MouseScrollDelta::PixelDelta(position) => position.y as f32 / 20.0,
+                        };
+                        let projection = if current_cam == FREE_ROAM_CAM { free_roam_cam.projection_mut() } else { hero_cam.projection_mut() };
+                        projection.zoom(controls.zoom(lines));
+                    },
+                    WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+                        pick_requested = true;
+                    },
                     _ => (),
                 }
             },
@@ -388,29 +622,10 @@ fn main() {
                 match event {
                     DeviceEvent::MouseMotion { delta: (y_delta, x_delta) } => {
                         let current_camera: &mut dyn KinematicCamera = if current_cam == FREE_ROAM_CAM { &mut free_roam_cam } else { &mut hero_cam };
-                        current_camera.rotate( (x_delta as f32).to_radians(), (-y_delta as f32).to_radians());
-                        for p in &mut axis_painters {
-                            p.binder_mut().update_uniform(
-                                "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                            )
-                        }
-                        test_triangle.binder_mut().update_uniform(
-                            "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                        );
-                        test_triangle.binder_mut().update_uniform(
-                            "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                        );
-                        skybox_painter.binder_mut().update_uniform(
-                            "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                        );
-                        sphere_painter.binder_mut().update_uniform(
-                            "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                        );
-                        labyrinth_painter.binder_mut().update_uniform(
-                            "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                        );
+                        current_camera.rotate(controls.rotation(x_delta), controls.rotation(-y_delta));
                     }
                     DeviceEvent::Key(KeyboardInput{ state: ElementState::Pressed, virtual_keycode: Some(key_code), .. }) => {
+                        free_roam_cam.camera_mut().set_speed(controls.move_step());
                         match key_code {
                             VirtualKeyCode::A => free_roam_cam.fixed_move(&Direction::Left),
                             VirtualKeyCode::D => free_roam_cam.fixed_move(&Direction::Right),
@@ -427,6 +642,25 @@ fn main() {
                                     draw_mode = DrawMode::Triangles;
                                 }
                             },
+                            VirtualKeyCode::F12 => take_screenshot = true,
+                            VirtualKeyCode::V => split_screen = !split_screen,
+                            VirtualKeyCode::F5 => match free_roam_cam.camera().save(CAMERA_POSE_PATH.as_ref()) {
+                                Ok(()) => println!("\nCamera pose saved to {}", CAMERA_POSE_PATH),
+                                Err(error) => eprintln!("\nFailed to save camera pose: {}", error),
+                            },
+                            VirtualKeyCode::F9 => match free_roam_cam.camera_mut().load(CAMERA_POSE_PATH.as_ref()) {
+                                Ok(()) => (),
+                                Err(error) => eprintln!("\nFailed to load camera pose: {}", error),
+                            },
+                            VirtualKeyCode::Equals | VirtualKeyCode::NumpadAdd | VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => {
+                                let steps = if matches!(key_code, VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract) { -1.0 } else { 1.0 };
+                                let projection = if current_cam == FREE_ROAM_CAM { free_roam_cam.projection_mut() } else { hero_cam.projection_mut() };
+                                projection.zoom(controls.zoom(steps));
+                            },
+                            VirtualKeyCode::P => render_loop.set_mode(match render_loop.mode() {
+                                RedrawMode::OnEvent => RedrawMode::Continuous,
+                                RedrawMode::Continuous => RedrawMode::OnEvent,
+                            }),
                             VirtualKeyCode::Escape => {
                                 if current_cam == FREE_ROAM_CAM {
                                     current_cam = HERO_CAM;
@@ -437,7 +671,7 @@ fn main() {
                             _ => (),
                         };
                         {
-                            let pos = free_roam_cam.get_position().as_ref().clone();
+                            let pos = free_roam_cam.get_position();
                             let center = free_roam_cam.get_position();
                             let collider_sphere = colliders::sphere::Sphere::new(center, 0.0);
                             let mut collision_detected = false;
@@ -445,72 +679,27 @@ fn main() {
                                 for collider in triangle_colliders {
                                     if collider.do_collide(&collider_sphere) {
                                         collision_detected = true;
-                                        test_triangle.binder_mut().update_uniform(
+                                        test_triangle.borrow_mut().binder_mut().update_uniform(
                                             COLOR_ID, Box::new([1.0, 0.0, 0.0]),
                                         );
                                     }
                                 }
                             }
                             if !collision_detected {
-                                test_triangle.binder_mut().update_uniform(
+                                test_triangle.borrow_mut().binder_mut().update_uniform(
                                     COLOR_ID, Box::new([0.0, 1.0, 0.0]),
                                 );
                             }
 
-                            let current_camera: &mut dyn KinematicCamera = if current_cam == FREE_ROAM_CAM { &mut free_roam_cam } else { &mut hero_cam };
-                            for p in &mut axis_painters {
-                                p.binder_mut().update_uniform(
-                                    "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                                )
-                            }
-                            skybox_painter.binder_mut().update_uniform(
-                                "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                            );
-                            sphere_painter.binder_mut().update_uniform(
-                                "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
-                            );
-                            sphere_painter.binder_mut().update_uniform(
-                                PLAYER_POSITION_ID, Box::new(pos)
-                            );
-                            labyrinth_painter.binder_mut().update_uniform(
-                                "view_matrix", Box::new(current_camera.view_matrix().as_ref().clone()),
+                            sphere_painter.borrow_mut().binder_mut().update_uniform(
+                                PLAYER_POSITION_ID, Box::new(Vec3Uniform(pos))
                             );
                         }
                     }
                     _ => (),
                 }
             }
-            Event::RedrawRequested(_) => {
-                unsafe {
-                    gl::ClearColor(Scene::LIGHT_BLUE.x, Scene::DARK_GRAY.y, Scene::LIGHT_BLUE.z, 1.0);
-                    gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-                }
-                gl_context.swap_buffers().unwrap();
-            }
             _ => (),
         }
-        fps_counter += 1;
-        if frame_rate_display + Duration::from_millis(500) < Instant::now() {
-            frame_rate_display = Instant::now();
-            print!("\r{} fps", fps_counter * 2);
-            std::io::stdout().flush().unwrap();
-            fps_counter = 0;
-        }
-
-        unsafe { gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT); }
-
-        labyrinth_painter.update_draw_mode(draw_mode);
-        sphere_painter.update_draw_mode(draw_mode);
-        test_triangle.draw();
-
-        labyrinth_painter.draw();
-        if current_cam != FREE_ROAM_CAM {
-            sphere_painter.draw();
-        }
-        for painter in &axis_painters {
-            painter.draw();
-        }
-        skybox_painter.draw();
-        gl_context.swap_buffers().unwrap();
     });
 }
@@ -1,26 +1,98 @@
-use crate::{binder, vertex, program, uniform, drawing, index_buffer};
+use crate::{binder, vertex, program, uniform, drawing, index_buffer, camera, stencil, render_state, geometry, gpu_timer};
 
 use crate::gl_assert_no_err;
 use uniform::Uniform;
 use program::Program;
-use drawing::DrawMode;
+use drawing::{DrawMode, PolygonMode, PolygonModeScopedBinder, LineWidthScopedBinder, PointSizeScopedBinder, PointSpriteScopedBinder, Winding, WindingScopedBinder, DoubleSidedScopedBinder, ScissorScopedBinder, Rect, DrawElementsIndirectCommand, IndirectBuffer, DrawCall};
 use index_buffer::{IndexBuffer, IndexingMode, IndexType, IndexBufferObject};
 use vertex::{VertexAttribute, BufferObject};
 use binder::Binder;
+use camera::Projection;
+use stencil::StencilState;
+use render_state::RenderState;
+use geometry::{Aabb, Frustum};
+use gpu_timer::QueryHandle;
 
+use nalgebra_glm as glm;
+
+
+/// Part of shared index buffer drawn by painter, see `Painter::sub_range`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SubRange {
+    pub first_index: usize,
+    pub index_count: usize,
+    pub base_vertex: i32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum InstanceCount {
+    Explicit(usize),
+    FromBuffers,
+}
+
+/// Flat id program used to render painter into picking buffer.
+struct Picking {
+    id: u32,
+    program: Program,
+}
+
+impl Picking {
+    const OBJECT_ID: &'static str = "object_id";
+}
 
 pub struct Painter<I: IndexBuffer> {
     binder: Binder<I>,
     draw_mode: DrawMode,
-    instance_count: Option<usize>,
+    instance_count: Option<InstanceCount>,
+    base_instance: usize,
+    polygon_mode: PolygonMode,
+    winding: Winding,
+    double_sided: bool,
+    line_width: Option<f32>,
+    point_size: Option<f32>,
+    point_sprite_size: Option<f32>,
+    stencil: Option<StencilState>,
+    render_state: RenderState,
+    scissor: Option<Rect>,
+    model: Option<glm::Mat4>,
+    bounds: Option<Aabb>,
+    projection: Option<Projection>,
+    sub_range: Option<SubRange>,
+    indirect: Option<IndirectBuffer>,
+    picking: Option<Picking>,
+    /// Depth only program drawing painter into `ShadowMap`.
+    shadow_program: Option<Program>,
 }
 
 impl<I: IndexBuffer> Painter<I> {
+    pub const MODEL_MATRIX_ID: &'static str = "model_matrix";
+    pub const PROJECTION_MATRIX_ID: &'static str = "perspective_matrix";
+    pub const VIEW_MATRIX_ID: &'static str = "view_matrix";
+    pub const POINT_SIZE_ID: &'static str = "point_size";
+    pub const DOUBLE_SIDED_ID: &'static str = "double_sided";
+
     pub fn new(binder: Binder<I>, draw_mode: DrawMode) -> Self {
         Self {
             binder,
             draw_mode,
-            instance_count: None
+            instance_count: None,
+            base_instance: 0,
+            polygon_mode: PolygonMode::default(),
+            winding: Winding::default(),
+            double_sided: false,
+            line_width: None,
+            point_size: None,
+            point_sprite_size: None,
+            stencil: None,
+            render_state: RenderState::default(),
+            scissor: None,
+            model: None,
+            bounds: None,
+            projection: None,
+            sub_range: None,
+            indirect: None,
+            picking: None,
+            shadow_program: None,
         }
     }
 
@@ -31,42 +103,476 @@ impl<I: IndexBuffer> Painter<I> {
     pub fn binder_mut(&mut self) -> &mut Binder<I> { &mut self.binder }
 
     pub fn instanced(mut self, instance_count: usize) -> Self {
-        self.instance_count = Some(instance_count);
+        self.instance_count = Some(InstanceCount::Explicit(instance_count));
+        self
+    }
+
+    /// Draw `count` instances starting at instance `base`, lets painters share one instance buffer.
+    pub fn instanced_range(mut self, base: usize, count: usize) -> Self {
+        self.instance_count = Some(InstanceCount::Explicit(count));
+        self.base_instance = base;
+        self
+    }
+
+    pub fn base_instance(&self) -> usize {
+        self.base_instance
+    }
+
+    /// Draw only `index_count` indices from `first_index` offset by `base_vertex`, for sub-meshes merged
+    /// into one vertex and index buffer. Requires indexed binder, instanced painters draw every instance of the range.
+    pub fn sub_range(mut self, first_index: usize, index_count: usize, base_vertex: i32) -> Self {
+        assert!(self.binder.index_type().is_some(), "sub range drawing requires an index buffer");
+        assert!(
+            first_index + index_count <= self.binder.vertex_count(),
+            "sub range {}..{} exceeds {} indices", first_index, first_index + index_count, self.binder.vertex_count()
+        );
+        self.sub_range = Some(SubRange { first_index, index_count, base_vertex });
+        self
+    }
+
+    /// Source draws from gpu side command buffer, requires indexed binder.
+    pub fn indirect(mut self, commands: &[DrawElementsIndirectCommand]) -> Self {
+        assert!(self.binder.index_type().is_some(), "indirect drawing requires an index buffer");
+        self.indirect = Some(IndirectBuffer::create(commands));
+        self
+    }
+
+    /// Make painter selectable with `picking::Picker`.
+    ///
+    /// `program` must accept painter's attributes and uniforms at the same locations as the regular program
+    /// and write `uniform uint object_id` into an integer output, usually it pairs painter's
+    /// vertex shader with `shaders/picking_f.glsl`.
+    pub fn pickable(mut self, id: u32, program: Program) -> Self {
+        assert_ne!(id, crate::picking::Picker::NO_OBJECT, "picking id 0 is reserved for background");
+        self.picking = Some(Picking { id, program });
+        self
+    }
+
+    pub fn picking_id(&self) -> Option<u32> {
+        self.picking.as_ref().map(|picking| picking.id)
+    }
+
+    /// Draw painter's id with picking program, does nothing for painters that are not pickable.
+    pub fn draw_picking(&self) {
+        if let Some(ref picking) = self.picking {
+            let _program_binder = picking.program.scoped_binder();
+            self.binder.bind_uniforms();
+            if let Some(location) = picking.program.uniform_location(Picking::OBJECT_ID) {
+                gl_assert_no_err!();
+                unsafe { gl::Uniform1ui(location, picking.id); }
+                gl_assert_no_err!();
+            }
+            self.draw_with_current_program();
+        }
+    }
+
+    /// Second pass of selection outline drawn with `program` reading the same attributes and uniform locations,
+    /// e.g. `programs::sphere_outline`, only where painter's own draw did not write its stencil reference.
+    pub fn draw_outline(&self, program: &Program) {
+        let stencil = self.stencil.expect("outlined painter must write stencil, see `StencilState::write`");
+        let _program_binder = program.scoped_binder();
+        self.binder.bind_uniforms();
+        self.with_draw_state(|| {
+            let _outline_binder = StencilState::not_equal(stencil.reference).scoped_binder();
+            self.issue();
+        });
+    }
+
+    /// Cast shadows with `program` which must share painter's vertex shader, e.g. `programs::sphere_shadow`.
+    pub fn shadow_caster(mut self, program: Program) -> Self {
+        self.shadow_program = Some(program);
+        self
+    }
+
+    pub fn is_shadow_caster(&self) -> bool {
+        self.shadow_program.is_some()
+    }
+
+    /// Draw painter's depth as seen from the light during `ShadowMap::render`, does nothing for painters
+    /// that do not cast shadows.
+    ///
+    /// Light matrices are bound in place of camera uniforms of the caster program only, painter's own uniforms
+    /// are left intact.
+    pub fn draw_shadow(&self, light_view: &glm::Mat4, light_projection: &glm::Mat4) {
+        if let Some(ref program) = self.shadow_program {
+            let _program_binder = program.scoped_binder();
+            self.binder.bind_uniforms();
+            self.with_draw_state(|| {
+                let locations = [Self::VIEW_MATRIX_ID, Self::PROJECTION_MATRIX_ID]
+                    .map(|ident| self.binder.uniform_location(ident));
+                for (location, matrix) in locations.into_iter().zip([light_view, light_projection]) {
+                    let location = location.expect("shadow casters require camera uniforms");
+                    matrix.as_ref().bind(location as _);
+                }
+                self.issue();
+            });
+        }
+    }
+
+    /// Draw with another program reading the same attributes and uniform locations.
+    pub fn draw_with_program(&self, program: &Program) {
+        let _program_binder = program.scoped_binder();
+        self.binder.bind_uniforms();
+        self.draw_with_current_program();
+    }
+
+    /// Draw as many instances as binder's instanced buffers hold.
+    pub fn instanced_by_buffers(mut self) -> Self {
+        assert!(self.binder.instance_count().is_some(), "binder contains no instanced buffers");
+        self.instance_count = Some(InstanceCount::FromBuffers);
         self
     }
 
+    pub fn instance_count(&self) -> Option<usize> {
+        self.instance_count.map(|instance_count| match instance_count {
+            InstanceCount::Explicit(count) => count,
+            InstanceCount::FromBuffers => self.binder.instance_count()
+                .expect("binder contains no instanced buffers"),
+        })
+    }
+
+    /// Width of rasterized lines, see `LineWidthScopedBinder` for driver limits.
+    pub fn line_width(mut self, line_width: f32) -> Self {
+        self.line_width = Some(line_width);
+        self
+    }
+
+    /// Size of rasterized points, ignored by programs writing `gl_PointSize`.
+    pub fn point_size(mut self, point_size: f32) -> Self {
+        self.point_size = Some(point_size);
+        self
+    }
+
+    /// Draw vertices as round sprites whose size in pixels at unit distance is `size`.
+    ///
+    /// Size is uploaded as `point_size` uniform registered after already present uniforms, meant for
+    /// `shaders/point_sprite_v.glsl` with camera matrices and color as the preceding uniforms.
+    /// Soft edges write alpha, enable blending to see them.
+    pub fn point_sprites(mut self, size: f32) -> Self {
+        if self.binder.uniform_location(Self::POINT_SIZE_ID).is_none() {
+            self.binder.add_uniform(Self::POINT_SIZE_ID, Box::new(size));
+        }
+        self.draw_mode = DrawMode::Points;
+        self.point_sprite_size = Some(size);
+        self
+    }
+
+    fn bind_point_sprite_size(&self) {
+        if let Some(size) = self.point_sprite_size {
+            let location = self.binder.uniform_location(Self::POINT_SIZE_ID)
+                .expect("point size uniform is registered together with point sprites");
+            size.bind(location as _);
+        }
+    }
+
+    /// Enable stencil test with given state while drawing this painter.
+    pub fn stencil(mut self, state: StencilState) -> Self {
+        self.stencil = Some(state);
+        self
+    }
+
+    /// Apply fixed function state such as depth function while drawing this painter.
+    pub fn render_state(mut self, state: RenderState) -> Self {
+        self.render_state = state;
+        self
+    }
+
+    /// Restrict drawing to window space rectangle with origin in bottom-left corner.
+    pub fn scissor(mut self, x: i32, y: i32, width: i32, height: i32) -> Self {
+        self.scissor = Some(Rect::new(x, y, width, height));
+        self
+    }
+
+    /// Draw edges of primitives instead of filling them, keeps primitive topology intact unlike `DrawMode::LineStrip`.
+    pub fn wireframe(self, enabled: bool) -> Self {
+        self.polygon_mode(if enabled { PolygonMode::Line } else { PolygonMode::Fill })
+    }
+
+    pub fn polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    pub fn update_polygon_mode(&mut self, new: PolygonMode) {
+        self.polygon_mode = new;
+    }
+
+    /// Winding of painter's front faces, matters once face culling is enabled.
+    pub fn winding(mut self, winding: Winding) -> Self {
+        self.winding = winding;
+        self
+    }
+
+    /// Draw both faces of primitives with culling disabled, e.g. for leaves or labyrinth triangles.
+    ///
+    /// Program must declare `double_sided` bool uniform after already present uniforms, it is set only
+    /// while drawing, see `DoubleSidedScopedBinder`. Fragment shader should pass its normals through
+    /// `facing_normal` from `shaders/double_sided.glsl` so that back faces are lit from the viewer's side.
+    pub fn double_sided(mut self, enabled: bool) -> Self {
+        if enabled && self.binder.uniform_location(Self::DOUBLE_SIDED_ID).is_none() {
+            self.binder.add_uniform(Self::DOUBLE_SIDED_ID, Box::new(false));
+        }
+        self.double_sided = enabled;
+        self
+    }
+
+    pub fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    /// Attach model matrix to the painter, it is uploaded on every draw.
+    ///
+    /// Matrix is registered as `model_matrix` uniform after already present uniforms
+    /// so shader should declare it at the next free location.
+    pub fn with_model(mut self, model: glm::Mat4) -> Self {
+        self.set_model(model);
+        self
+    }
+
+    pub fn set_model(&mut self, model: glm::Mat4) {
+        if self.binder.uniform_location(Self::MODEL_MATRIX_ID).is_none() {
+            self.binder.add_uniform(Self::MODEL_MATRIX_ID, Box::new(*model.as_ref()));
+        }
+        self.model = Some(model);
+    }
+
+    pub fn model(&self) -> Option<&glm::Mat4> {
+        self.model.as_ref()
+    }
+
+    fn bind_model(&self) {
+        if let Some(ref model) = self.model {
+            let location = self.binder.uniform_location(Self::MODEL_MATRIX_ID)
+                .expect("model matrix uniform is registered together with the model");
+            model.as_ref().bind(location as _);
+        }
+    }
+
+    /// Model space bounds used for frustum culling, painters without bounds are never culled.
+    pub fn with_bounds(mut self, bounds: Aabb) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Bounds transformed by painter's model matrix.
+    pub fn world_bounds(&self) -> Option<Aabb> {
+        self.bounds.map(|bounds| match self.model {
+            Some(ref model) => bounds.transformed(model),
+            None => bounds,
+        })
+    }
+
+    pub fn is_visible(&self, frustum: &Frustum) -> bool {
+        self.world_bounds().map_or(true, |bounds| frustum.intersects_aabb(&bounds))
+    }
+
+    /// Override projection shared through binder's uniforms for this painter only, e.g. orthographic
+    /// projection for hud elements drawn next to perspective scene.
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.set_projection(projection);
+        self
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        if self.binder.uniform_location(Self::PROJECTION_MATRIX_ID).is_none() {
+            self.binder.add_uniform(Self::PROJECTION_MATRIX_ID, Box::new(*projection.matrix().as_ref()));
+        }
+        self.projection = Some(projection);
+    }
+
+    pub fn projection(&self) -> Option<&Projection> {
+        self.projection.as_ref()
+    }
+
+    fn bind_projection(&self) {
+        if let Some(ref projection) = self.projection {
+            let location = self.binder.uniform_location(Self::PROJECTION_MATRIX_ID)
+                .expect("projection matrix uniform is registered together with the projection");
+            projection.matrix().as_ref().bind(location as _);
+        }
+    }
+
     pub fn update_draw_mode(&mut self, new: DrawMode) {
         self.draw_mode = new;
     }
 
     pub fn draw(&self) {
-        let _draw_scoped_binder = self.binder.draw_binder();
-        match (self.instance_count, self.binder.index_type()) {
-            (Some(instance_count), Some(ref index_type)) => {
-                drawing::instanced::draw_indexed(
-                    &self.draw_mode,
-                    self.binder.vertex_count(),
-                    index_type,
-                    instance_count
-                );
-            },
-            (Some(instance_count), None) => {
-                drawing::instanced::draw_arrays(
-                    &self.draw_mode,
-                    self.binder.vertex_count(),
-                    instance_count
-                );
-            },
-            (None, Some(ref index_type)) => {
-                drawing::draw_indexed(
-                    &self.draw_mode,
-                    self.binder.vertex_count(),
-                    index_type
-                );
-            },
-            (None, None) => {
-                drawing::draw_arrays(&self.draw_mode, self.binder.vertex_count());
+        let _program_binder = self.binder.program_binder();
+        self.binder.sync_uniforms();
+        self.draw_with_current_program();
+    }
+
+    /// Draw measuring gpu time, poll the handle a frame later to find expensive painters.
+    pub fn draw_timed(&self) -> QueryHandle {
+        QueryHandle::measure(|| self.draw())
+    }
+
+    /// Draw `count` vertices, or indices for indexed painters, starting at `first`, e.g. single lod of merged mesh.
+    ///
+    /// Range replaces painter's `sub_range` except for its base vertex.
+    pub fn draw_range(&self, first: usize, count: usize) {
+        assert!(self.indirect.is_none(), "indirect painters can not draw a range");
+        debug_assert!(
+            first + count <= self.binder.vertex_count(),
+            "range {}..{} exceeds {} vertices of the painter", first, first + count, self.binder.vertex_count()
+        );
+        let mut draw_call = self.draw_call().first(first);
+        draw_call.count = count;
+        let _program_binder = self.binder.program_binder();
+        self.binder.sync_uniforms();
+        self.with_draw_state(|| draw_call.issue());
+    }
+
+    /// Draw assuming that painter's program is already in use.
+    pub(crate) fn draw_with_current_program(&self) {
+        self.with_draw_state(|| self.issue());
+    }
+
+    fn issue(&self) {
+        if let Some(ref commands) = self.indirect {
+            let index_type = self.binder.index_type().expect("indirect drawing requires an index buffer");
+            drawing::draw_indexed_indirect(&self.draw_mode, &index_type, commands);
+        } else {
+            self.draw_call().issue();
+        }
+    }
+
+    /// Bind painter's vao, fixed function state and per draw uniforms around `issue`.
+    fn with_draw_state(&self, issue: impl FnOnce()) {
+        let _vao_binder = self.binder.vao_binder();
+        let _polygon_mode_binder = PolygonModeScopedBinder::new(self.polygon_mode);
+        let _winding_binder = (self.winding != Winding::Ccw).then(|| WindingScopedBinder::new(self.winding));
+        let _double_sided_binder = self.double_sided.then(|| {
+            let location = self.binder.uniform_location(Self::DOUBLE_SIDED_ID)
+                .expect("double sided uniform is registered together with the flag");
+            DoubleSidedScopedBinder::new(location as _)
+        });
+        let _line_width_binder = self.line_width.map(LineWidthScopedBinder::new);
+        let _point_size_binder = self.point_size.map(PointSizeScopedBinder::new);
+        let _point_sprite_binder = self.point_sprite_size.map(|_| PointSpriteScopedBinder::new());
+        let _stencil_binder = self.stencil.as_ref().map(StencilState::scoped_binder);
+        let _render_state_binder = (!self.render_state.is_default()).then(|| self.render_state.scoped_binder());
+        let _scissor_binder = self.scissor.map(ScissorScopedBinder::new);
+        self.bind_model();
+        self.bind_projection();
+        self.bind_point_sprite_size();
+        self.binder.bind_time_varying_uniforms();
+        issue();
+    }
+
+    /// Draw described by painter's configuration, indirect draws are not covered.
+    pub fn draw_call(&self) -> DrawCall {
+        let mut draw_call = match (self.binder.index_type(), self.sub_range) {
+            (Some(index_type), Some(range)) => DrawCall::indexed(self.draw_mode, range.index_count, index_type)
+                .first(range.first_index)
+                .base_vertex(range.base_vertex),
+            (Some(index_type), None) => DrawCall::indexed(self.draw_mode, self.binder.vertex_count(), index_type),
+            (None, _) => DrawCall::arrays(self.draw_mode, self.binder.vertex_count()),
+        };
+        if let Some(instance_count) = self.instance_count() {
+            draw_call = draw_call.instanced(instance_count, self.base_instance);
+        }
+        draw_call
+    }
+}
+
+impl<I: IndexBuffer> crate::Drawable for Painter<I> {
+    fn draw(&self) {
+        Painter::draw(self)
+    }
+
+    /// Camera uniforms the program does not declare are skipped.
+    fn set_camera(&mut self, view: &glm::Mat4, projection: &glm::Mat4) {
+        if self.binder.uniform_location(Self::VIEW_MATRIX_ID).is_some() {
+            self.binder.update_uniform(Self::VIEW_MATRIX_ID, Box::new(*view.as_ref()));
+        }
+        if self.binder.uniform_location(Self::PROJECTION_MATRIX_ID).is_some() {
+            self.binder.update_uniform(Self::PROJECTION_MATRIX_ID, Box::new(*projection.as_ref()));
+        }
+    }
+}
+
+/// Painters reordered so that painters sharing a program are drawn under single program bind.
+///
+/// Within each program group painters are sorted by vertex array to minimize vao switches.
+pub struct PainterBatch<I: IndexBuffer> {
+    painters: Vec<Painter<I>>,
+    draw_order: Vec<usize>,
+}
+
+impl<I: IndexBuffer> PainterBatch<I> {
+    pub fn new(painters: Vec<Painter<I>>) -> Self {
+        let mut batch = Self { painters, draw_order: Vec::new() };
+        batch.sort();
+        batch
+    }
+
+    fn sort_key(painter: &Painter<I>) -> (gl::types::GLuint, gl::types::GLuint) {
+        (painter.binder.program().id(), painter.binder.vao().id())
+    }
+
+    fn sort(&mut self) {
+        let mut draw_order = (0..self.painters.len()).collect::<Vec<_>>();
+        draw_order.sort_by_key(|index| Self::sort_key(&self.painters[*index]));
+        self.draw_order = draw_order;
+    }
+
+    pub fn push(&mut self, painter: Painter<I>) {
+        self.painters.push(painter);
+        self.sort();
+    }
+
+    pub fn painters(&self) -> &[Painter<I>] {
+        &self.painters
+    }
+
+    /// Painters can be mutated but not replaced, so their programs and therefore order stay valid.
+    pub fn painters_mut(&mut self) -> impl Iterator<Item=&mut Painter<I>> {
+        self.painters.iter_mut()
+    }
+
+    /// Indices into `painters` in order they are drawn.
+    pub fn draw_order(&self) -> &[usize] {
+        &self.draw_order
+    }
+
+    pub fn draw_all(&self) {
+        self.draw_filtered(|_| true);
+    }
+
+    /// Draw painters whose bounds intersect the frustum.
+    pub fn draw_culled(&self, frustum: &Frustum) {
+        self.draw_filtered(|painter| painter.is_visible(frustum));
+    }
+
+    fn draw_filtered(&self, filter: impl Fn(&Painter<I>) -> bool) {
+        let mut remaining = self.draw_order.as_slice();
+        while let Some(first) = remaining.first() {
+            let program = self.painters[*first].binder.program();
+            let group_len = remaining.iter()
+                .take_while(|index| self.painters[**index].binder.program().id() == program.id())
+                .count();
+            let (group, rest) = remaining.split_at(group_len);
+            remaining = rest;
+
+            let visible = group.iter()
+                .map(|index| &self.painters[*index])
+                .filter(|painter| filter(painter))
+                .collect::<Vec<_>>();
+            if visible.is_empty() {
+                continue;
+            }
+            let _program_binder = program.scoped_binder();
+            for painter in visible {
+                painter.binder.sync_uniforms();
+                painter.draw_with_current_program();
             }
         }
     }
 }
+
+impl<I: IndexBuffer> crate::Drawable for PainterBatch<I> {
+    fn draw(&self) {
+        self.draw_all()
+    }
+}
@@ -0,0 +1,67 @@
+use crate::framebuffer::Framebuffer;
+use crate::gl_assert_no_err;
+
+use glutin::dpi::PhysicalPosition;
+
+
+/// Object selection by rendering object ids into an integer color buffer.
+///
+/// Pickable painters (see `Painter::pickable`) write their id, id 0 is reserved for background.
+pub struct Picker {
+    framebuffer: Framebuffer,
+}
+
+impl Picker {
+    pub const NO_OBJECT: u32 = 0;
+
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { framebuffer: Framebuffer::new(width, height, gl::R32UI) }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.framebuffer.size()
+    }
+
+    /// Recreate id buffer for new window size so that window positions map onto its pixels.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if self.size() != (width, height) && width > 0 && height > 0 {
+            self.framebuffer = Framebuffer::new(width, height, gl::R32UI);
+        }
+    }
+
+    /// Clear id buffer and run `draw` with it bound, `draw` should issue `Painter::draw_picking` calls.
+    pub fn render(&self, draw: impl FnOnce()) {
+        let _framebuffer_binder = self.framebuffer.scoped_binder();
+        let clear_id = [Self::NO_OBJECT; 4];
+        gl_assert_no_err!();
+        unsafe {
+            gl::ClearBufferuiv(gl::COLOR, 0, clear_id.as_ptr());
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+        gl_assert_no_err!();
+        draw();
+    }
+
+    /// Id of object at given window position (origin in top left corner), `None` for background.
+    pub fn read(&self, position: PhysicalPosition<f64>) -> Option<u32> {
+        let (width, height) = self.size();
+        if position.x < 0.0 || position.y < 0.0 || position.x >= width as f64 || position.y >= height as f64 {
+            return None;
+        }
+        let x = position.x as i32;
+        let y = height as i32 - 1 - position.y as i32;
+
+        let mut id = Self::NO_OBJECT;
+        let mut previous_read = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer.id());
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            gl::ReadPixels(x, y, 1, 1, gl::RED_INTEGER, gl::UNSIGNED_INT, &mut id as *mut u32 as *mut std::ffi::c_void);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read as _);
+        }
+        gl_assert_no_err!();
+        if id == Self::NO_OBJECT { None } else { Some(id) }
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+
+const INCLUDE_DIRECTIVE: &str = "#include";
+
+/// Shader source with `#include` directives resolved.
+///
+/// Spliced files are separated with `#line <line> <source>` directives, `<source>` indexes `files`
+/// so that `0(12)` in driver compile log refers to line 12 of `files[0]`.
+#[derive(Debug, Clone)]
+pub struct Preprocessed {
+    pub source: String,
+    pub files: Vec<PathBuf>,
+}
+
+impl Preprocessed {
+    /// Legend mapping source string numbers used in `#line` directives to file paths.
+    pub fn source_map(&self) -> String {
+        self.files.iter()
+            .enumerate()
+            .map(|(index, file)| format!("{}: {}\n", index, file.display()))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum PreprocessError {
+    Io { path: PathBuf, error: std::io::Error },
+    MalformedInclude { path: PathBuf, line: usize },
+    IncludeCycle(Vec<PathBuf>),
+}
+
+impl Display for PreprocessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreprocessError::Io { path, error } => write!(f, "failed to read {}: {}", path.display(), error),
+            PreprocessError::MalformedInclude { path, line } => {
+                write!(f, "{}:{}: expected #include \"<path>\"", path.display(), line)
+            }
+            PreprocessError::IncludeCycle(chain) => {
+                let chain = chain.iter().map(|path| path.display().to_string()).collect::<Vec<_>>();
+                write!(f, "include cycle: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError { }
+
+/// Read shader file splicing every `#include "path"` directive, paths are relative to the including file.
+pub fn preprocess(path: &Path) -> Result<Preprocessed, PreprocessError> {
+    let read_file = |path: &Path| {
+        let canonical = path.canonicalize()
+            .map_err(|error| PreprocessError::Io { path: path.to_owned(), error })?;
+        let source = std::fs::read_to_string(path)
+            .map_err(|error| PreprocessError::Io { path: path.to_owned(), error })?;
+        Ok((canonical, source))
+    };
+    splice_root(path, &read_file)
+}
+
+/// Splice shader embedded in the executable, e.g. with `include_str!`.
+///
+/// `path` only names the shader, included files are looked up among `includes` by path
+/// (relative to `path` like in `preprocess`) instead of being read from disk.
+pub fn preprocess_embedded(path: &Path, source: &str, includes: &[(&str, &str)]) -> Result<Preprocessed, PreprocessError> {
+    let lookup = |included: &Path| {
+        if included == path {
+            return Ok((included.to_owned(), source.to_owned()));
+        }
+        includes.iter()
+            .find(|(include_path, _)| Path::new(include_path) == included)
+            .map(|(_, include_source)| (included.to_owned(), include_source.to_string()))
+            .ok_or_else(|| PreprocessError::Io {
+                path: included.to_owned(),
+                error: std::io::Error::new(std::io::ErrorKind::NotFound, "not embedded"),
+            })
+    };
+    splice_root(path, &lookup)
+}
+
+/// Source of shader at given path together with path used to detect include cycles.
+type Loader<'a> = dyn Fn(&Path) -> Result<(PathBuf, String), PreprocessError> + 'a;
+
+fn splice_root(path: &Path, load: &Loader) -> Result<Preprocessed, PreprocessError> {
+    let mut preprocessed = Preprocessed { source: String::new(), files: Vec::new() };
+    let mut include_stack = Vec::new();
+    splice(path, load, &mut include_stack, &mut preprocessed)?;
+    Ok(preprocessed)
+}
+
+fn splice(path: &Path, load: &Loader, include_stack: &mut Vec<PathBuf>, output: &mut Preprocessed) -> Result<(), PreprocessError> {
+    let (canonical, source) = load(path)?;
+    if include_stack.contains(&canonical) {
+        let mut chain = include_stack.clone();
+        chain.push(canonical);
+        return Err(PreprocessError::IncludeCycle(chain));
+    }
+
+    let source_index = output.files.len();
+    output.files.push(path.to_owned());
+    include_stack.push(canonical);
+
+    // root file must start with #version so it gets no leading #line directive.
+    if source_index != 0 {
+        output.source.push_str(&format!("#line 1 {}\n", source_index));
+    }
+    for (line_index, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(argument) = trimmed.strip_prefix(INCLUDE_DIRECTIVE) {
+            let included = parse_include_argument(argument)
+                .ok_or_else(|| PreprocessError::MalformedInclude { path: path.to_owned(), line: line_index + 1 })?;
+            let included = path.parent().unwrap_or(Path::new("")).join(included);
+            splice(&included, load, include_stack, output)?;
+            // resume numbering of the including file at the line after the directive.
+            output.source.push_str(&format!("#line {} {}\n", line_index + 2, source_index));
+        } else {
+            output.source.push_str(line);
+            output.source.push('\n');
+        }
+    }
+
+    include_stack.pop();
+    Ok(())
+}
+
+fn parse_include_argument(argument: &str) -> Option<&str> {
+    argument.trim()
+        .strip_prefix('"')?
+        .strip_suffix('"')
+}
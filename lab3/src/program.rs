@@ -1,6 +1,11 @@
 use gl;
 use gl::types::GLuint;
 use std::ffi::{CString, CStr};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use crate::{state_cache, preprocessor};
+use crate::program_cache::ProgramCache;
+use crate::shader_loader::ShaderLoader;
 
 pub struct Shader {
     id: GLuint,
@@ -34,6 +39,65 @@ impl Drop for Shader {
     }
 }
 
+/// Pipeline stage of a shader, used to tell which of program's files failed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    /// Get opengl defined numerical value of given variant
+    pub fn get(&self) -> gl::types::GLenum {
+        match *self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+            ShaderStage::Compute => gl::COMPUTE_SHADER,
+        }
+    }
+}
+
+impl Display for ShaderStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex"),
+            ShaderStage::Fragment => write!(f, "fragment"),
+            ShaderStage::Compute => write!(f, "compute"),
+        }
+    }
+}
+
+/// Failure to load program's shader files, compile and link errors still panic with the driver's log.
+#[derive(Debug)]
+pub enum ProgramError {
+    /// Shader file or one of its includes does not exist, `path` is the resolved path.
+    FileNotFound { path: PathBuf, stage: ShaderStage },
+    Preprocess { stage: ShaderStage, error: preprocessor::PreprocessError },
+}
+
+impl ProgramError {
+    pub(crate) fn from_preprocess(error: preprocessor::PreprocessError, stage: ShaderStage) -> Self {
+        match error {
+            preprocessor::PreprocessError::Io { path, error } if error.kind() == std::io::ErrorKind::NotFound => {
+                ProgramError::FileNotFound { path, stage }
+            }
+            error => ProgramError::Preprocess { stage, error },
+        }
+    }
+}
+
+impl Display for ProgramError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramError::FileNotFound { path, stage } => write!(f, "{} shader {} not found", stage, path.display()),
+            ProgramError::Preprocess { stage, error } => write!(f, "failed to preprocess {} shader: {}", stage, error),
+        }
+    }
+}
+
+impl std::error::Error for ProgramError { }
+
 fn shader_from_source(source: &CStr, kind: gl::types::GLenum) -> Result<gl::types::GLuint, String> {
     let id = unsafe { gl::CreateShader(kind) };
     unsafe {
@@ -78,34 +142,160 @@ fn create_whitespace_cstring_with_len(len: usize) -> CString {
     unsafe { CString::from_vec_unchecked(buffer) }
 }
 
-pub struct ScopedBinder(GLuint);
+fn link_status(program_id: GLuint) -> Result<(), String> {
+    let mut success: gl::types::GLint = 1;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
+    }
+
+    if success == 0 {
+        let mut len: gl::types::GLint = 0;
+        unsafe {
+            gl::GetProgramiv(program_id, gl::INFO_LOG_LENGTH, &mut len);
+        }
+
+        let error = create_whitespace_cstring_with_len(len as usize);
+
+        unsafe {
+            gl::GetProgramInfoLog(
+                program_id,
+                len,
+                std::ptr::null_mut(),
+                error.as_ptr() as *mut gl::types::GLchar
+            );
+        }
+
+        return Err(error.to_string_lossy().into_owned());
+    }
+
+    Ok(())
+}
+
+/// Keeps program in use and restores previously used one on drop so that scopes can nest,
+/// borrows the program so that it cannot be deleted while the scope is open.
+///
+/// Outermost scope leaves its program in use so that consecutive draws with the same program skip
+/// `glUseProgram`, `Program::drop` stops using the program before deleting it.
+pub struct ScopedBinder<'a> {
+    id: GLuint,
+    previous: GLuint,
+    _program: std::marker::PhantomData<&'a Program>,
+}
 
-impl ScopedBinder {
-    pub fn new(program_id: GLuint) -> Self {
+impl<'a> ScopedBinder<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self::bind(program.id())
+    }
+
+    fn bind(program_id: GLuint) -> Self {
         log::debug!("Binding program {}", program_id);
-        unsafe { gl::UseProgram(program_id); }
-        Self(program_id)
+        let previous = state_cache::current().program;
+        state_cache::use_program(program_id);
+        Self { id: program_id, previous, _program: std::marker::PhantomData }
     }
 }
 
-impl Drop for ScopedBinder {
+impl Drop for ScopedBinder<'_> {
     fn drop(&mut self) {
-        log::debug!("Unbinding program {}", self.0);
-        unsafe { gl::UseProgram(0) }
+        if self.previous != 0 {
+            log::debug!("Unbinding program {}, restoring {}", self.id, self.previous);
+            state_cache::use_program(self.previous);
+        }
     }
 }
 
+/// Description of active uniform as reported by `glGetActiveUniform`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UniformInfo {
+    pub name: String,
+    pub location: gl::types::GLint,
+    pub gl_type: gl::types::GLenum,
+    /// Array length, 1 for non array uniforms.
+    pub size: gl::types::GLint,
+}
+
+/// Description of active vertex attribute as reported by `glGetActiveAttrib`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AttributeInfo {
+    pub name: String,
+    pub location: gl::types::GLint,
+    pub gl_type: gl::types::GLenum,
+    pub size: gl::types::GLint,
+}
+
 pub struct Program {
     id: gl::types::GLuint,
 }
 
 impl Program {
-    pub fn from_file(vertex: &std::path::Path, fragment: &std::path::Path) -> Self {
-        let v_code = std::fs::read_to_string(vertex).unwrap();
-        let f_code = std::fs::read_to_string(fragment).unwrap();
+    /// Compile and link program from shader files, `#include "path"` directives are resolved by `preprocessor`.
+    ///
+    /// Relative paths are resolved by default `ShaderLoader`, use its `program` for other directories.
+    pub fn from_file(vertex: &std::path::Path, fragment: &std::path::Path) -> Result<Self, ProgramError> {
+        Self::from_file_cached(vertex, fragment, None)
+    }
 
-        let v_code_raw = CString::new(v_code).unwrap();
-        let f_code_raw = CString::new(f_code).unwrap();
+    /// Like `from_file` but with opt-in binary cache, linked program is loaded from `cache` if present
+    /// and stored there otherwise.
+    pub fn from_file_cached(
+        vertex: &std::path::Path,
+        fragment: &std::path::Path,
+        cache: Option<&ProgramCache>
+    ) -> Result<Self, ProgramError> {
+        let loader = ShaderLoader::default();
+        Self::from_resolved_files(&loader.resolve(vertex), &loader.resolve(fragment), cache)
+    }
+
+    /// Like `from_file_cached` with paths already resolved by a `ShaderLoader`.
+    pub(crate) fn from_resolved_files(
+        vertex: &std::path::Path,
+        fragment: &std::path::Path,
+        cache: Option<&ProgramCache>
+    ) -> Result<Self, ProgramError> {
+        let v_code = Self::preprocess_file(vertex, ShaderStage::Vertex)?;
+        let f_code = Self::preprocess_file(fragment, ShaderStage::Fragment)?;
+
+        let cache = cache.map(|cache| (cache, cache.key(&[&v_code.source, &f_code.source])));
+        if let Some((cache, key)) = cache {
+            if let Some(program) = cache.load(key) {
+                return Ok(program);
+            }
+        }
+
+        let v_shader = Self::compile_preprocessed(&v_code, gl::VERTEX_SHADER);
+        let f_shader = Self::compile_preprocessed(&f_code, gl::FRAGMENT_SHADER);
+
+        let program = Self::link(&[&v_shader, &f_shader], cache.is_some()).unwrap();
+        if let Some((cache, key)) = cache {
+            if let Err(error) = cache.store(key, &program) {
+                log::warn!("Failed to cache program binary: {}", error);
+            }
+        }
+        Ok(program)
+    }
+
+    fn preprocess_file(path: &std::path::Path, stage: ShaderStage) -> Result<preprocessor::Preprocessed, ProgramError> {
+        preprocessor::preprocess(path).map_err(|error| ProgramError::from_preprocess(error, stage))
+    }
+
+    /// Compile and link already preprocessed sources, e.g. shaders embedded by `programs`.
+    pub fn from_preprocessed(vertex: &preprocessor::Preprocessed, fragment: &preprocessor::Preprocessed) -> Self {
+        let v_shader = Self::compile_preprocessed(vertex, gl::VERTEX_SHADER);
+        let f_shader = Self::compile_preprocessed(fragment, gl::FRAGMENT_SHADER);
+        Self::link(&[&v_shader, &f_shader], false).unwrap()
+    }
+
+    /// Compile spliced source, compile log refers to files by source string numbers so the legend is appended.
+    fn compile_preprocessed(preprocessed: &preprocessor::Preprocessed, kind: gl::types::GLenum) -> Shader {
+        let code_raw = CString::new(preprocessed.source.as_str()).unwrap();
+        Shader::from_source(code_raw.as_ref(), kind)
+            .unwrap_or_else(|error| panic!("{}\nsource strings:\n{}", error, preprocessed.source_map()))
+    }
+
+    /// Compile and link program from vertex and fragment shader sources, e.g. embedded with `include_str!`.
+    pub fn from_source(vertex_source: &str, fragment_source: &str) -> Self {
+        let v_code_raw = CString::new(vertex_source).unwrap();
+        let f_code_raw = CString::new(fragment_source).unwrap();
 
         let v_shader = Shader::from_source(v_code_raw.as_ref(), gl::VERTEX_SHADER).unwrap();
         let f_shader = Shader::from_source(f_code_raw.as_ref(), gl::FRAGMENT_SHADER).unwrap();
@@ -117,39 +307,139 @@ impl Program {
         self.id
     }
 
-    pub fn from_shaders(shaders: &[&Shader]) -> Result<Program, String> {
-        let program_id = unsafe { gl::CreateProgram() };
+    /// Query location of uniform with given name, `None` if program has no such active uniform.
+    pub fn uniform_location(&self, name: &str) -> Option<gl::types::GLint> {
+        let name = CString::new(name).unwrap();
+        let location = unsafe { gl::GetUniformLocation(self.id, name.as_ptr()) };
+        if location < 0 { None } else { Some(location) }
+    }
 
-        for shader in shaders {
-            unsafe { gl::AttachShader(program_id, shader.id()); }
+    /// Query location of vertex attribute with given name, `None` if program has no such active attribute.
+    pub fn attribute_location(&self, name: &str) -> Option<gl::types::GLint> {
+        let name = CString::new(name).unwrap();
+        let location = unsafe { gl::GetAttribLocation(self.id, name.as_ptr()) };
+        if location < 0 { None } else { Some(location) }
+    }
+
+    /// Query all active vertex attributes of linked program, sorted by location.
+    pub fn active_attributes(&self) -> Vec<AttributeInfo> {
+        let mut count = 0;
+        let mut max_name_len = 0;
+        unsafe {
+            gl::GetProgramiv(self.id, gl::ACTIVE_ATTRIBUTES, &mut count);
+            gl::GetProgramiv(self.id, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_name_len);
         }
 
-        unsafe { gl::LinkProgram(program_id); }
+        let mut attributes = (0..count as GLuint)
+            .map(|index| {
+                let mut name_buffer = vec![0u8; max_name_len.max(1) as usize];
+                let mut name_len = 0;
+                let mut size = 0;
+                let mut gl_type = 0;
+                unsafe {
+                    gl::GetActiveAttrib(
+                        self.id,
+                        index,
+                        name_buffer.len() as _,
+                        &mut name_len,
+                        &mut size,
+                        &mut gl_type,
+                        name_buffer.as_mut_ptr() as *mut gl::types::GLchar,
+                    );
+                }
+                name_buffer.truncate(name_len as usize);
+                let name = String::from_utf8_lossy(&name_buffer).into_owned();
+                let location = self.attribute_location(&name).unwrap_or(-1);
+                AttributeInfo { name, location, gl_type, size }
+            })
+            .collect::<Vec<_>>();
+        attributes.sort_by_key(|attribute| attribute.location);
+        attributes
+    }
 
-        let mut success: gl::types::GLint = 1;
+    /// Query all active uniforms of linked program, sorted by location.
+    pub fn active_uniforms(&self) -> Vec<UniformInfo> {
+        let mut count = 0;
+        let mut max_name_len = 0;
         unsafe {
-            gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
+            gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORMS, &mut count);
+            gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_name_len);
         }
 
-        if success == 0 {
-            eprintln!("===== Compilation Error! ===== ");
-            let mut len: gl::types::GLint = 0;
-            unsafe {
-                gl::GetProgramiv(program_id, gl::INFO_LOG_LENGTH, &mut len);
-            }
+        let mut uniforms = (0..count as GLuint)
+            .map(|index| {
+                let mut name_buffer = vec![0u8; max_name_len.max(1) as usize];
+                let mut name_len = 0;
+                let mut size = 0;
+                let mut gl_type = 0;
+                unsafe {
+                    gl::GetActiveUniform(
+                        self.id,
+                        index,
+                        name_buffer.len() as _,
+                        &mut name_len,
+                        &mut size,
+                        &mut gl_type,
+                        name_buffer.as_mut_ptr() as *mut gl::types::GLchar,
+                    );
+                }
+                name_buffer.truncate(name_len as usize);
+                let name = String::from_utf8_lossy(&name_buffer).into_owned();
+                let location = self.uniform_location(&name).unwrap_or(-1);
+                UniformInfo { name, location, gl_type, size }
+            })
+            .collect::<Vec<_>>();
+        uniforms.sort_by_key(|uniform| uniform.location);
+        uniforms
+    }
 
-            let error = create_whitespace_cstring_with_len(len as usize);
+    pub fn from_shaders(shaders: &[&Shader]) -> Result<Program, String> {
+        Self::link(shaders, false)
+    }
 
+    /// Link program whose listed vertex shader outputs are captured by transform feedback.
+    ///
+    /// Varyings are registered before linking, `interleaved` writes all of them into single buffer,
+    /// otherwise each one goes to buffer bound at its index, see `TransformFeedback`.
+    pub fn from_shaders_with_varyings(shaders: &[&Shader], varyings: &[&str], interleaved: bool) -> Result<Program, String> {
+        let buffer_mode = if interleaved { gl::INTERLEAVED_ATTRIBS } else { gl::SEPARATE_ATTRIBS };
+        Self::link_with_varyings(shaders, false, Some((varyings, buffer_mode)))
+    }
+
+    /// Link shaders into program, `binary_retrievable` hints the driver that `binary` will be queried.
+    fn link(shaders: &[&Shader], binary_retrievable: bool) -> Result<Program, String> {
+        Self::link_with_varyings(shaders, binary_retrievable, None)
+    }
+
+    fn link_with_varyings(
+        shaders: &[&Shader],
+        binary_retrievable: bool,
+        varyings: Option<(&[&str], gl::types::GLenum)>,
+    ) -> Result<Program, String> {
+        let program_id = unsafe { gl::CreateProgram() };
+
+        for shader in shaders {
+            unsafe { gl::AttachShader(program_id, shader.id()); }
+        }
+
+        if let Some((varyings, buffer_mode)) = varyings {
+            let names = varyings.iter().map(|name| CString::new(*name).unwrap()).collect::<Vec<_>>();
+            let pointers = names.iter().map(|name| name.as_ptr()).collect::<Vec<_>>();
             unsafe {
-                gl::GetProgramInfoLog(
-                    program_id,
-                    len,
-                    std::ptr::null_mut(),
-                    error.as_ptr() as *mut gl::types::GLchar
-                );
+                gl::TransformFeedbackVaryings(program_id, pointers.len() as _, pointers.as_ptr(), buffer_mode);
             }
+        }
 
-            return Err(error.to_string_lossy().into_owned());
+        unsafe {
+            if binary_retrievable {
+                gl::ProgramParameteri(program_id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as _);
+            }
+            gl::LinkProgram(program_id);
+        }
+
+        if let Err(error) = link_status(program_id) {
+            eprintln!("===== Compilation Error! ===== ");
+            return Err(error);
         }
 
         for shader in shaders {
@@ -159,20 +449,157 @@ impl Program {
         Ok(Program { id: program_id })
     }
 
+    /// Retrieve driver specific binary of linked program as `(format, binary)`.
+    ///
+    /// `None` if driver supports no binary formats.
+    pub fn binary(&self) -> Option<(gl::types::GLenum, Vec<u8>)> {
+        let mut format_count = 0;
+        let mut length = 0;
+        unsafe {
+            gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut format_count);
+            gl::GetProgramiv(self.id, gl::PROGRAM_BINARY_LENGTH, &mut length);
+        }
+        if format_count == 0 || length == 0 {
+            return None;
+        }
+
+        let mut binary = vec![0u8; length as usize];
+        let mut written = 0;
+        let mut format = 0;
+        unsafe {
+            gl::GetProgramBinary(
+                self.id,
+                length,
+                &mut written,
+                &mut format,
+                binary.as_mut_ptr() as *mut std::ffi::c_void
+            );
+        }
+        binary.truncate(written as usize);
+        Some((format, binary))
+    }
+
+    /// Load program from binary previously returned by `binary`.
+    ///
+    /// Drivers reject binaries produced by different driver version or hardware, caller should then
+    /// fall back to compiling from source.
+    pub fn from_binary(format: gl::types::GLenum, binary: &[u8]) -> Result<Program, String> {
+        let program_id = unsafe { gl::CreateProgram() };
+        unsafe {
+            gl::ProgramBinary(program_id, format, binary.as_ptr() as *const std::ffi::c_void, binary.len() as _);
+        }
+        match link_status(program_id) {
+            Ok(()) => Ok(Program { id: program_id }),
+            Err(error) => {
+                unsafe { gl::DeleteProgram(program_id); }
+                Err(error)
+            }
+        }
+    }
+
     fn new(v_shader: &Shader, f_shader: &Shader) -> Self {
         Program::from_shaders(&[v_shader, f_shader]).unwrap()
     }
 
-    pub fn scoped_binder(&self) -> ScopedBinder {
-        ScopedBinder::new(self.id)
+    pub fn scoped_binder(&self) -> ScopedBinder<'_> {
+        ScopedBinder::new(self)
     }
 }
 
 impl Drop for Program {
     fn drop(&mut self) {
         log::debug!("Destroying program {}", self.id);
+        if self.id == 0 {
+            return;
+        }
         unsafe {
             gl::DeleteProgram(self.id);
         }
+        // deleting program in use only flags it for deletion, unbind it so that it is actually released.
+        if state_cache::current().program == self.id {
+            state_cache::use_program(0);
+        }
+    }
+}
+
+/// Program consisting of single compute shader.
+pub struct ComputeProgram {
+    program: Program,
+}
+
+impl ComputeProgram {
+    /// Compile and link compute shader file, paths and `#include` directives are resolved like in `Program::from_file`.
+    pub fn from_file(compute: &std::path::Path) -> Result<Self, ProgramError> {
+        Self::from_resolved_file(&ShaderLoader::default().resolve(compute))
+    }
+
+    /// Like `from_file` with path already resolved by a `ShaderLoader`.
+    pub(crate) fn from_resolved_file(compute: &std::path::Path) -> Result<Self, ProgramError> {
+        let code = Program::preprocess_file(compute, ShaderStage::Compute)?;
+        let shader = Program::compile_preprocessed(&code, ShaderStage::Compute.get());
+        Ok(Self { program: Program::link(&[&shader], false).unwrap() })
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Run `x * y * z` work groups and wait with subsequent storage buffer accesses until they finish writing.
+    ///
+    /// Buffers read or written by the shader must be bound beforehand, e.g. with `ShaderStorageBuffer::bind`.
+    pub fn dispatch(&self, x: GLuint, y: GLuint, z: GLuint) {
+        let _program_binder = self.program.scoped_binder();
+        log::debug!("Dispatching {}x{}x{} work groups of program {}", x, y, z, self.program.id());
+        unsafe {
+            gl::DispatchCompute(x, y, z);
+            gl::MemoryBarrier(gl::SHADER_STORAGE_BARRIER_BIT | gl::BUFFER_UPDATE_BARRIER_BIT);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use state_cache::calls::{self, Call};
+
+    #[test]
+    fn nested_scoped_binder_restores_outer_program() {
+        let outer = ScopedBinder::bind(1);
+        {
+            let _inner = ScopedBinder::bind(2);
+            assert_eq!(state_cache::current().program, 2);
+        }
+        assert_eq!(state_cache::current().program, 1);
+        drop(outer);
+        assert_eq!(calls::take(), [Call::UseProgram(1), Call::UseProgram(2), Call::UseProgram(1)]);
+    }
+
+    #[test]
+    fn consecutive_scopes_of_same_program_use_it_once() {
+        for _ in 0..3 {
+            let _binder = ScopedBinder::bind(1);
+        }
+        let _other = ScopedBinder::bind(2);
+        assert_eq!(calls::take(), [Call::UseProgram(1), Call::UseProgram(2)]);
+    }
+
+    #[test]
+    fn missing_shader_file_is_reported_with_its_stage() {
+        let missing = std::path::Path::new("/nonexistent/missing_v.glsl");
+        match Program::from_file(missing, "missing_f.glsl".as_ref()) {
+            Err(ProgramError::FileNotFound { path, stage: ShaderStage::Vertex }) => assert_eq!(path, missing),
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("program built from missing file"),
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn missing_compute_shader_file_is_reported_with_its_stage() {
+        let missing = std::path::Path::new("/nonexistent/missing_c.glsl");
+        match ComputeProgram::from_file(missing) {
+            Err(ProgramError::FileNotFound { path, stage: ShaderStage::Compute }) => assert_eq!(path, missing),
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("program built from missing file"),
+        }
+    }
+}
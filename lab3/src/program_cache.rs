@@ -0,0 +1,92 @@
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::CStr;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::program::Program;
+
+
+const MAGIC: &[u8; 4] = b"GLPB";
+const HEADER_LEN: usize = MAGIC.len() + std::mem::size_of::<u32>();
+
+/// On disk cache of linked program binaries.
+///
+/// Entries are keyed by hash of shader sources together with vendor, renderer and version strings of
+/// current context, so updating the driver results in a miss instead of feeding it a foreign binary.
+/// Binaries the driver still rejects are evicted and the program is compiled from source.
+pub struct ProgramCache {
+    directory: PathBuf,
+}
+
+impl ProgramCache {
+    pub fn new(directory: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self { directory })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Cache key of program linked from given sources with current context.
+    pub fn key(&self, sources: &[&str]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for name in [gl::VENDOR, gl::RENDERER, gl::VERSION] {
+            context_string(name).hash(&mut hasher);
+        }
+        sources.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn entry_path(&self, key: u64) -> PathBuf {
+        self.directory.join(format!("{:016x}.bin", key))
+    }
+
+    /// Load cached program, `None` on miss or when the driver rejected cached binary.
+    pub fn load(&self, key: u64) -> Option<Program> {
+        let path = self.entry_path(key);
+        let bytes = std::fs::read(&path).ok()?;
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            log::warn!("Evicting malformed program binary {}", path.display());
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        let format = u32::from_le_bytes(bytes[MAGIC.len()..HEADER_LEN].try_into().unwrap());
+        match Program::from_binary(format, &bytes[HEADER_LEN..]) {
+            Ok(program) => {
+                log::debug!("Loaded program binary {}", path.display());
+                Some(program)
+            }
+            Err(error) => {
+                log::warn!("Driver rejected program binary {}: {}", path.display(), error);
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Store binary of linked program, program must be linked with binary retrievable hint.
+    pub fn store(&self, key: u64, program: &Program) -> std::io::Result<()> {
+        let (format, binary) = match program.binary() {
+            Some(binary) => binary,
+            None => return Ok(()),
+        };
+        let mut bytes = Vec::with_capacity(HEADER_LEN + binary.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&format.to_le_bytes());
+        bytes.extend_from_slice(&binary);
+        std::fs::write(self.entry_path(key), bytes)
+    }
+}
+
+fn context_string(name: gl::types::GLenum) -> String {
+    unsafe {
+        let string = gl::GetString(name);
+        if string.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(string as *const _).to_string_lossy().into_owned()
+        }
+    }
+}
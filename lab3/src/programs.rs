@@ -0,0 +1,123 @@
+//! Programs built from shaders embedded in the executable, they do not depend on working directory.
+//!
+//! Shader files are still the source of truth, use `Program::from_file` to load modified copies.
+
+use std::path::Path;
+
+use crate::preprocessor;
+use crate::program::{Program, ProgramError, ShaderStage};
+
+
+/// Files available to `#include` directives of embedded shaders, paths as seen from the crate root.
+const INCLUDES: &[(&str, &str)] = &[
+    ("shaders/transform.glsl", include_str!("../shaders/transform.glsl")),
+    ("shaders/shadow.glsl", include_str!("../shaders/shadow.glsl")),
+    ("shaders/double_sided.glsl", include_str!("../shaders/double_sided.glsl")),
+];
+
+/// Build program from embedded `(path, source)` pairs, unresolved includes are reported as `ProgramError`.
+fn try_embedded(vertex: (&str, &str), fragment: (&str, &str)) -> Result<Program, ProgramError> {
+    let preprocess = |(path, source): (&str, &str), stage| {
+        preprocessor::preprocess_embedded(Path::new(path), source, INCLUDES)
+            .map_err(|error| ProgramError::from_preprocess(error, stage))
+    };
+    Ok(Program::from_preprocessed(
+        &preprocess(vertex, ShaderStage::Vertex)?,
+        &preprocess(fragment, ShaderStage::Fragment)?,
+    ))
+}
+
+/// Like `try_embedded` but panics on unresolved includes.
+fn embedded(vertex: (&str, &str), fragment: (&str, &str)) -> Program {
+    try_embedded(vertex, fragment).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Colors interpolated between vertices, position at location 0 and color at location 1,
+/// uniforms `perspective_matrix` at location 0 and `view_matrix` at location 1.
+pub fn vertex_color() -> Program {
+    embedded(
+        ("shaders/vertex_color_v.glsl", include_str!("../shaders/vertex_color_v.glsl")),
+        ("shaders/line_f.glsl", include_str!("../shaders/line_f.glsl")),
+    )
+}
+
+/// Single color from `color` uniform at location 2, position at location 0,
+/// uniforms `perspective_matrix` at location 0 and `view_matrix` at location 1.
+pub fn flat_color() -> Program {
+    embedded(
+        ("shaders/flat_v.glsl", include_str!("../shaders/flat_v.glsl")),
+        ("shaders/line_f.glsl", include_str!("../shaders/line_f.glsl")),
+    )
+}
+
+/// Program of `geometry::basic_triangle`.
+pub fn triangle() -> Program {
+    embedded(
+        ("shaders/triangle_v.glsl", include_str!("../shaders/triangle_v.glsl")),
+        ("shaders/triangle_f.glsl", include_str!("../shaders/triangle_f.glsl")),
+    )
+}
+
+/// Program of `geometry::sphere`.
+pub fn sphere() -> Result<Program, ProgramError> {
+    try_embedded(
+        ("shaders/sphere_v.glsl", include_str!("../shaders/sphere_v.glsl")),
+        ("shaders/sphere_f.glsl", include_str!("../shaders/sphere_f.glsl")),
+    )
+}
+
+/// Program of `geometry::labyrinth`.
+pub fn labyrinth() -> Program {
+    embedded(
+        ("shaders/labyrinth_v.glsl", include_str!("../shaders/labyrinth_v.glsl")),
+        ("shaders/labyrinth_f.glsl", include_str!("../shaders/labyrinth_f.glsl")),
+    )
+}
+
+/// Depth only fragment shader of shadow casters, paired with vertex shader of the cast geometry.
+const SHADOW_DEPTH_FRAGMENT: (&str, &str) = ("shaders/shadow_depth_f.glsl", include_str!("../shaders/shadow_depth_f.glsl"));
+
+/// Shadow caster program of `geometry::sphere`, see `Painter::shadow_caster`.
+pub fn sphere_shadow() -> Program {
+    embedded(("shaders/sphere_v.glsl", include_str!("../shaders/sphere_v.glsl")), SHADOW_DEPTH_FRAGMENT)
+}
+
+/// Shadow caster program of `geometry::labyrinth`, see `Painter::shadow_caster`.
+pub fn labyrinth_shadow() -> Program {
+    embedded(("shaders/labyrinth_v.glsl", include_str!("../shaders/labyrinth_v.glsl")), SHADOW_DEPTH_FRAGMENT)
+}
+
+/// Second pass of `geometry::sphere` selection outline, see `Painter::draw_outline`.
+pub fn sphere_outline() -> Program {
+    embedded(
+        ("shaders/sphere_outline_v.glsl", include_str!("../shaders/sphere_outline_v.glsl")),
+        ("shaders/outline_f.glsl", include_str!("../shaders/outline_f.glsl")),
+    )
+}
+
+/// Glyph quads of `TextRenderer`, vertex `(x, y, u, v)` at location 0,
+/// uniforms `projection_matrix` at location 0 and `text_color` at location 1, atlas at texture unit 0.
+pub fn text() -> Program {
+    embedded(
+        ("shaders/text_v.glsl", include_str!("../shaders/text_v.glsl")),
+        ("shaders/text_f.glsl", include_str!("../shaders/text_f.glsl")),
+    )
+}
+
+/// Geometry pass of `DeferredRenderer`, position at location 0 and normal at location 1,
+/// uniforms `perspective_matrix` at location 0, `view_matrix` at location 1 and `albedo` at location 2.
+pub fn gbuffer() -> Program {
+    embedded(
+        ("shaders/gbuffer_v.glsl", include_str!("../shaders/gbuffer_v.glsl")),
+        ("shaders/gbuffer_f.glsl", include_str!("../shaders/gbuffer_f.glsl")),
+    )
+}
+
+/// Full screen lighting pass of `DeferredRenderer`, G-buffer position, normal and albedo at texture units 0 to 2,
+/// lights are uploaded by `Lighting::upload`.
+pub fn deferred_lighting() -> Program {
+    embedded(
+        ("shaders/fullscreen_v.glsl", include_str!("../shaders/fullscreen_v.glsl")),
+        ("shaders/deferred_lighting_f.glsl", include_str!("../shaders/deferred_lighting_f.glsl")),
+    )
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+use glutin::event::Event;
+use glutin::event_loop::ControlFlow;
+use glutin::window::Window;
+
+
+/// When render loop produces new frames.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RedrawMode {
+    /// Redraw every iteration of event loop, required by animations.
+    Continuous,
+    /// Sleep until input arrives and redraw once per batch of events, saves battery for static scenes.
+    OnEvent,
+}
+
+/// Drives update and draw phases from glutin's event loop.
+///
+/// Frame is rendered exclusively in response to `RedrawRequested` which is requested after
+/// `MainEventsCleared`, so every frame runs update once followed by draw once.
+pub struct RenderLoop {
+    mode: RedrawMode,
+    last_update: Instant,
+    pending_events: bool,
+}
+
+impl RenderLoop {
+    pub fn new(mode: RedrawMode) -> Self {
+        Self { mode, last_update: Instant::now(), pending_events: true }
+    }
+
+    pub fn mode(&self) -> RedrawMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: RedrawMode) {
+        self.mode = mode;
+        self.pending_events = true;
+    }
+
+    /// Handle frame related events, `update` receives time elapsed since its previous call.
+    pub fn handle<T>(
+        &mut self,
+        event: &Event<T>,
+        window: &Window,
+        control_flow: &mut ControlFlow,
+        mut update: impl FnMut(Duration),
+        mut draw: impl FnMut(),
+    ) {
+        if *control_flow != ControlFlow::Exit {
+            *control_flow = match self.mode {
+                RedrawMode::Continuous => ControlFlow::Poll,
+                RedrawMode::OnEvent => ControlFlow::Wait,
+            };
+        }
+        match event {
+            Event::WindowEvent { .. } | Event::DeviceEvent { .. } => self.pending_events = true,
+            Event::MainEventsCleared => {
+                if self.mode == RedrawMode::Continuous || self.pending_events {
+                    let now = Instant::now();
+                    update(now - self.last_update);
+                    self.last_update = now;
+                    window.request_redraw();
+                }
+            },
+            Event::RedrawRequested(_) => {
+                self.pending_events = false;
+                draw();
+            },
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use gl::types::GLenum;
+use crate::gl_assert_no_err;
+
+
+/// Opengl depth comparison function enumeration.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DepthFunc {
+    Never,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Always,
+}
+
+impl DepthFunc {
+    /// Get opengl defined numerical value of given variant
+    pub fn get(&self) -> GLenum {
+        match *self {
+            DepthFunc::Never => gl::NEVER,
+            DepthFunc::Less => gl::LESS,
+            DepthFunc::LessEqual => gl::LEQUAL,
+            DepthFunc::Greater => gl::GREATER,
+            DepthFunc::GreaterEqual => gl::GEQUAL,
+            DepthFunc::Equal => gl::EQUAL,
+            DepthFunc::NotEqual => gl::NOTEQUAL,
+            DepthFunc::Always => gl::ALWAYS,
+        }
+    }
+
+    /// Variant of opengl defined numerical value, `None` for values which are not depth functions.
+    pub fn from_gl(func: GLenum) -> Option<Self> {
+        match func {
+            gl::NEVER => Some(DepthFunc::Never),
+            gl::LESS => Some(DepthFunc::Less),
+            gl::LEQUAL => Some(DepthFunc::LessEqual),
+            gl::GREATER => Some(DepthFunc::Greater),
+            gl::GEQUAL => Some(DepthFunc::GreaterEqual),
+            gl::EQUAL => Some(DepthFunc::Equal),
+            gl::NOTEQUAL => Some(DepthFunc::NotEqual),
+            gl::ALWAYS => Some(DepthFunc::Always),
+            _ => None,
+        }
+    }
+
+    /// Depth function of current context.
+    pub fn current() -> Self {
+        let mut func = 0;
+        gl_assert_no_err!();
+        unsafe { gl::GetIntegerv(gl::DEPTH_FUNC, &mut func); }
+        gl_assert_no_err!();
+        Self::from_gl(func as _).expect("context reports valid depth function")
+    }
+}
+
+impl Default for DepthFunc {
+    fn default() -> Self { DepthFunc::Less }
+}
+
+/// Depth offset `factor * slope + units * resolvable_difference` added to filled polygons, see `glPolygonOffset`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PolygonOffset {
+    pub factor: f32,
+    pub units: f32,
+}
+
+/// Per painter fixed function state which differs from opengl defaults, e.g. `GL_LEQUAL` for skyboxes.
+///
+/// ```ignore
+/// let skybox = painter.render_state(RenderState::default().depth_func(DepthFunc::LessEqual));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RenderState {
+    pub depth_func: DepthFunc,
+    /// Clamp depth instead of clipping against near and far planes, e.g. for shadow casters behind the light.
+    pub depth_clamp: bool,
+    /// Push filled polygons away from the camera so that coplanar overlays (wireframe, decals) win depth test.
+    ///
+    /// `None` keeps offset of the enclosing scope, e.g. casters drawn during `ShadowMap::render`.
+    pub polygon_offset: Option<PolygonOffset>,
+}
+
+impl RenderState {
+    pub fn depth_func(mut self, func: DepthFunc) -> Self {
+        self.depth_func = func;
+        self
+    }
+
+    pub fn depth_clamp(mut self, enabled: bool) -> Self {
+        self.depth_clamp = enabled;
+        self
+    }
+
+    pub fn polygon_offset(mut self, factor: f32, units: f32) -> Self {
+        self.polygon_offset = Some(PolygonOffset { factor, units });
+        self
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// State of current context, polygon offset is `None` when `GL_POLYGON_OFFSET_FILL` is disabled.
+    pub fn current() -> Self {
+        let mut factor = 0.0;
+        let mut units = 0.0;
+        gl_assert_no_err!();
+        let (depth_clamp, polygon_offset_fill) = unsafe {
+            gl::GetFloatv(gl::POLYGON_OFFSET_FACTOR, &mut factor);
+            gl::GetFloatv(gl::POLYGON_OFFSET_UNITS, &mut units);
+            (gl::IsEnabled(gl::DEPTH_CLAMP) == gl::TRUE, gl::IsEnabled(gl::POLYGON_OFFSET_FILL) == gl::TRUE)
+        };
+        gl_assert_no_err!();
+        Self {
+            depth_func: DepthFunc::current(),
+            depth_clamp,
+            polygon_offset: polygon_offset_fill.then_some(PolygonOffset { factor, units }),
+        }
+    }
+
+    fn apply(&self) {
+        gl_assert_no_err!();
+        unsafe {
+            gl::DepthFunc(self.depth_func.get());
+            if self.depth_clamp {
+                gl::Enable(gl::DEPTH_CLAMP);
+            } else {
+                gl::Disable(gl::DEPTH_CLAMP);
+            }
+            match self.polygon_offset {
+                Some(PolygonOffset { factor, units }) => {
+                    gl::Enable(gl::POLYGON_OFFSET_FILL);
+                    gl::PolygonOffset(factor, units);
+                }
+                None => {
+                    gl::Disable(gl::POLYGON_OFFSET_FILL);
+                    gl::PolygonOffset(0.0, 0.0);
+                }
+            }
+        }
+        gl_assert_no_err!();
+    }
+
+    pub fn scoped_binder(&self) -> ScopedBinder {
+        ScopedBinder::new(*self)
+    }
+}
+
+/// Applies given state, restores previous state on drop so that scopes can nest.
+pub struct ScopedBinder {
+    state: RenderState,
+    previous: RenderState,
+}
+
+impl ScopedBinder {
+    pub fn new(state: RenderState) -> Self {
+        let previous = RenderState::current();
+        let state = RenderState { polygon_offset: state.polygon_offset.or(previous.polygon_offset), ..state };
+        log::debug!("Applying {:?}", state);
+        state.apply();
+        Self { state, previous }
+    }
+}
+
+impl Drop for ScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring {:?} after {:?}", self.previous, self.state);
+        self.previous.apply();
+    }
+}
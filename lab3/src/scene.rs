@@ -0,0 +1,319 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::{Drawable, Transform};
+use crate::camera::CameraPose;
+use crate::gl_assert_no_err;
+
+use nalgebra_glm as glm;
+use serde::{Deserialize, Serialize};
+
+
+/// Handle of drawable added to `Scene`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DrawableId(usize);
+
+/// Persistable scene object, its drawable is rebuilt from `mesh` name by `MeshRegistry` so buffers are not stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectDescription {
+    pub mesh: String,
+    pub position: [f32; 3],
+    /// Euler angles in radians, see `Transform::matrix`.
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+    pub visible: bool,
+}
+
+impl ObjectDescription {
+    pub fn new(mesh: impl Into<String>, transform: &Transform) -> Self {
+        Self {
+            mesh: mesh.into(),
+            position: transform.position.into(),
+            rotation: transform.rotation.into(),
+            scale: transform.scale.into(),
+            visible: true,
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::new(self.position.into(), self.rotation.into(), self.scale.into())
+    }
+}
+
+/// Persistable scene, stored as ron, see `Scene::save` and `Scene::load`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneDescription {
+    pub bg_color: [f32; 4],
+    pub camera: CameraPose,
+    pub objects: Vec<ObjectDescription>,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+    UnknownMesh(String),
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "failed to access scene file: {}", error),
+            SceneError::Serialize(error) => write!(f, "failed to serialize scene: {}", error),
+            SceneError::Deserialize(error) => write!(f, "invalid scene file: {}", error),
+            SceneError::UnknownMesh(name) => write!(f, "mesh {} is not registered", name),
+        }
+    }
+}
+
+impl std::error::Error for SceneError { }
+
+impl From<std::io::Error> for SceneError {
+    fn from(error: std::io::Error) -> Self { SceneError::Io(error) }
+}
+
+/// Builds drawable of registered mesh placed with given model matrix.
+pub type DrawableFactory = Box<dyn Fn(&glm::Mat4) -> Box<dyn Drawable>>;
+
+/// Named meshes scene objects can refer to.
+#[derive(Default)]
+pub struct MeshRegistry {
+    factories: HashMap<String, DrawableFactory>,
+}
+
+impl MeshRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, factory: impl Fn(&glm::Mat4) -> Box<dyn Drawable> + 'static) {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    pub fn build(&self, object: &ObjectDescription) -> Result<Box<dyn Drawable>, SceneError> {
+        let factory = self.factories.get(&object.mesh)
+            .ok_or_else(|| SceneError::UnknownMesh(object.mesh.clone()))?;
+        Ok(factory(&object.transform().matrix()))
+    }
+}
+
+/// Drawables rendered together from single camera.
+///
+/// Camera matrices are pushed to every drawable only when they change, drawables that still need
+/// to be mutated after being added can be shared through `Rc<RefCell<_>>`.
+pub struct Scene {
+    bg_color: glm::Vec4,
+    view: glm::Mat4,
+    projection: glm::Mat4,
+    drawables: Vec<(Box<dyn Drawable>, bool)>,
+    /// Description of drawables added through `add_object`, others are not persisted.
+    objects: Vec<Option<ObjectDescription>>,
+}
+
+impl Scene {
+    pub const DARK_GRAY:  glm::Vec3 = glm::Vec3::new(0.23, 0.23, 0.23);
+    pub const LIGHT_BLUE: glm::Vec3 = glm::Vec3::new(0.54, 0.82, 1.0);
+
+    pub fn new(bg_color: glm::Vec4, view: glm::Mat4, projection: glm::Mat4) -> Self {
+        Self { bg_color, view, projection, drawables: Vec::new(), objects: Vec::new() }
+    }
+
+    pub fn bg_color(&self) -> &glm::Vec4 {
+        &self.bg_color
+    }
+
+    pub fn set_bg_color(&mut self, bg_color: glm::Vec4) {
+        self.bg_color = bg_color;
+    }
+
+    /// Add visible drawable pointed at scene's current camera.
+    pub fn add(&mut self, drawable: impl Drawable + 'static) -> DrawableId {
+        self.push(Box::new(drawable), None)
+    }
+
+    /// Add drawable built by registry which is persisted by `save`.
+    pub fn add_object(&mut self, registry: &MeshRegistry, object: ObjectDescription) -> Result<DrawableId, SceneError> {
+        let drawable = registry.build(&object)?;
+        let visible = object.visible;
+        let id = self.push(drawable, Some(object));
+        self.set_visible(id, visible);
+        Ok(id)
+    }
+
+    fn push(&mut self, mut drawable: Box<dyn Drawable>, object: Option<ObjectDescription>) -> DrawableId {
+        drawable.set_camera(&self.view, &self.projection);
+        self.drawables.push((drawable, true));
+        self.objects.push(object);
+        DrawableId(self.drawables.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.drawables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drawables.is_empty()
+    }
+
+    pub fn is_visible(&self, id: DrawableId) -> bool {
+        self.drawables[id.0].1
+    }
+
+    /// Hidden drawables are skipped by `draw` but still follow the camera.
+    pub fn set_visible(&mut self, id: DrawableId, visible: bool) {
+        self.drawables[id.0].1 = visible;
+        if let Some(ref mut object) = self.objects[id.0] {
+            object.visible = visible;
+        }
+    }
+
+    /// Persistable part of the scene, camera is owned by the caller.
+    pub fn description(&self, camera: CameraPose) -> SceneDescription {
+        SceneDescription {
+            bg_color: self.bg_color.into(),
+            camera,
+            objects: self.objects.iter().flatten().cloned().collect(),
+        }
+    }
+
+    /// Scene with objects of given description, camera pose should be applied by the caller
+    /// before passing its matrices.
+    pub fn from_description(
+        description: &SceneDescription,
+        registry: &MeshRegistry,
+        view: glm::Mat4,
+        projection: glm::Mat4,
+    ) -> Result<Self, SceneError> {
+        let mut scene = Self::new(description.bg_color.into(), view, projection);
+        for object in &description.objects {
+            scene.add_object(registry, object.clone())?;
+        }
+        Ok(scene)
+    }
+
+    /// Write background, camera and registry built objects as ron file.
+    pub fn save(&self, path: &Path, camera: CameraPose) -> Result<(), SceneError> {
+        let source = ron::ser::to_string_pretty(&self.description(camera), ron::ser::PrettyConfig::default())
+            .map_err(SceneError::Serialize)?;
+        std::fs::write(path, source)?;
+        Ok(())
+    }
+
+    /// Read description written by `save`, see `from_description`.
+    pub fn load(path: &Path) -> Result<SceneDescription, SceneError> {
+        let source = std::fs::read_to_string(path)?;
+        ron::from_str(&source).map_err(SceneError::Deserialize)
+    }
+
+    pub fn view(&self) -> &glm::Mat4 {
+        &self.view
+    }
+
+    pub fn projection(&self) -> &glm::Mat4 {
+        &self.projection
+    }
+
+    /// Update camera uniforms of all drawables, does nothing if matrices did not change.
+    pub fn set_camera(&mut self, view: &glm::Mat4, projection: &glm::Mat4) {
+        if self.view == *view && self.projection == *projection {
+            return;
+        }
+        self.view = *view;
+        self.projection = *projection;
+        for (drawable, _) in &mut self.drawables {
+            drawable.set_camera(view, projection);
+        }
+    }
+
+    /// Clear color, depth and stencil buffers using scene's background color.
+    pub fn clear(&self) {
+        gl_assert_no_err!();
+        unsafe {
+            gl::ClearColor(self.bg_color.x, self.bg_color.y, self.bg_color.z, self.bg_color.w);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+        }
+        gl_assert_no_err!();
+    }
+
+    /// Draw visible drawables in order they were added without clearing, e.g. once per viewport.
+    pub fn draw(&self) {
+        for (drawable, _) in self.drawables.iter().filter(|(_, visible)| *visible) {
+            drawable.draw();
+        }
+    }
+
+    pub fn render(&self) {
+        self.clear();
+        self.draw();
+    }
+}
+
+impl<D: Drawable> Drawable for Rc<RefCell<D>> {
+    fn draw(&self) {
+        self.borrow().draw()
+    }
+
+    fn set_camera(&mut self, view: &glm::Mat4, projection: &glm::Mat4) {
+        self.borrow_mut().set_camera(view, projection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Nothing;
+
+    impl Drawable for Nothing {
+        fn draw(&self) { }
+    }
+
+    fn registry() -> MeshRegistry {
+        let mut registry = MeshRegistry::new();
+        registry.register("cube", |_| Box::new(Nothing));
+        registry
+    }
+
+    fn camera() -> CameraPose {
+        CameraPose { position: [0.0, 1.0, 5.0], orientation: [0.0, 0.0, 0.0, 1.0], fovy: Some(0.8) }
+    }
+
+    #[test]
+    fn saved_scene_loads_into_same_description() {
+        let mut scene = Scene::new(glm::vec4(0.1, 0.2, 0.3, 1.0), glm::identity(), glm::identity());
+        let transform = Transform::new(glm::vec3(1.0, 2.0, 3.0), glm::vec3(0.0, 0.5, 0.0), glm::vec3(2.0, 2.0, 2.0));
+        scene.add_object(&registry(), ObjectDescription::new("cube", &transform)).unwrap();
+        let hidden = scene.add_object(&registry(), ObjectDescription::new("cube", &transform)).unwrap();
+        scene.set_visible(hidden, false);
+        scene.add(Nothing);
+
+        let path = std::env::temp_dir().join(format!("lab3_scene_round_trip_{}.ron", std::process::id()));
+        scene.save(&path, camera()).unwrap();
+        let loaded = Scene::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, scene.description(camera()));
+        assert_eq!(loaded.objects.len(), 2);
+        assert!(!loaded.objects[1].visible);
+    }
+
+    #[test]
+    fn description_of_unknown_mesh_fails_to_load() {
+        let description = SceneDescription {
+            bg_color: [0.0; 4],
+            camera: camera(),
+            objects: vec![ObjectDescription::new("teapot", &Transform::new(glm::zero(), glm::zero(), glm::vec3(1.0, 1.0, 1.0)))],
+        };
+        let scene = Scene::from_description(&description, &registry(), glm::identity(), glm::identity());
+        assert!(matches!(scene, Err(SceneError::UnknownMesh(mesh)) if mesh == "teapot"));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{painter, index_buffer};
+
+use painter::Painter;
+use index_buffer::IndexBuffer;
+
+use nalgebra_glm as glm;
+
+
+/// Node of transform hierarchy.
+///
+/// Each node stores transform relative to its parent, world transform is resolved during `draw`
+/// and set as model matrix of node's painter.
+pub struct SceneNode<I: IndexBuffer> {
+    local: glm::Mat4,
+    painter: Option<Painter<I>>,
+    children: Vec<SceneNode<I>>,
+}
+
+impl<I: IndexBuffer> SceneNode<I> {
+    pub fn new(local: glm::Mat4, painter: Option<Painter<I>>) -> Self {
+        Self { local, painter, children: Vec::new() }
+    }
+
+    pub fn empty(local: glm::Mat4) -> Self {
+        Self::new(local, None)
+    }
+
+    pub fn with_child(mut self, child: SceneNode<I>) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn add_child(&mut self, child: SceneNode<I>) {
+        self.children.push(child);
+    }
+
+    pub fn children(&self) -> &[SceneNode<I>] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut [SceneNode<I>] {
+        &mut self.children
+    }
+
+    pub fn local(&self) -> &glm::Mat4 {
+        &self.local
+    }
+
+    pub fn set_local(&mut self, local: glm::Mat4) {
+        self.local = local;
+    }
+
+    pub fn painter(&self) -> Option<&Painter<I>> {
+        self.painter.as_ref()
+    }
+
+    pub fn painter_mut(&mut self) -> Option<&mut Painter<I>> {
+        self.painter.as_mut()
+    }
+
+    pub fn world_matrix(&self, parent_world: &glm::Mat4) -> glm::Mat4 {
+        parent_world * self.local
+    }
+
+    pub fn draw(&mut self, parent_world: &glm::Mat4) {
+        let world = self.world_matrix(parent_world);
+        if let Some(ref mut painter) = self.painter {
+            painter.set_model(world);
+            painter.draw();
+        }
+        for child in &mut self.children {
+            child.draw(&world);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+use crate::gl_assert_no_err;
+
+use image::RgbaImage;
+
+
+/// Read `width` x `height` block of color buffer of currently bound read framebuffer starting at `x`, `y`.
+///
+/// Opengl rows start at the bottom of the screen, rows are flipped so that the image has top-left origin.
+/// Empty image is returned for zero sized block.
+pub fn capture_framebuffer(x: i32, y: i32, width: u32, height: u32) -> RgbaImage {
+    if width == 0 || height == 0 {
+        return RgbaImage::new(width, height);
+    }
+    let row_len = width as usize * 4;
+    let mut pixels = vec![0u8; row_len * height as usize];
+    gl_assert_no_err!();
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            x, y,
+            width as _, height as _,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr() as *mut std::ffi::c_void,
+        );
+    }
+    gl_assert_no_err!();
+
+    let flipped = pixels.chunks_exact(row_len)
+        .rev()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>();
+    RgbaImage::from_raw(width, height, flipped).expect("buffer size matches image dimensions")
+}
+
+/// Capture current viewport and save it as png.
+pub fn save_screenshot(path: &Path) -> image::ImageResult<()> {
+    let mut viewport = [0; 4];
+    unsafe { gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()); }
+    let [x, y, width, height] = viewport;
+    capture_framebuffer(x, y, width as _, height as _).save_with_format(path, image::ImageFormat::Png)
+}
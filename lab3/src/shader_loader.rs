@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+
+use crate::program::{Program, ComputeProgram, ProgramError};
+
+
+/// Resolves relative shader paths against a base directory, so shaders load independently of working directory.
+#[derive(Debug, Clone)]
+pub struct ShaderLoader {
+    base_dir: PathBuf,
+}
+
+impl ShaderLoader {
+    /// Environment variable overriding default base directory.
+    pub const ENV_VAR: &'static str = "SHADER_DIR";
+
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self { base_dir: base_dir.into() }
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// Relative paths are joined onto base directory, absolute paths are kept as they are.
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        self.base_dir.join(path)
+    }
+
+    pub fn program(&self, vertex: &Path, fragment: &Path) -> Result<Program, ProgramError> {
+        Program::from_resolved_files(&self.resolve(vertex), &self.resolve(fragment), None)
+    }
+
+    pub fn compute_program(&self, compute: &Path) -> Result<ComputeProgram, ProgramError> {
+        ComputeProgram::from_resolved_file(&self.resolve(compute))
+    }
+}
+
+impl Default for ShaderLoader {
+    /// `$SHADER_DIR` if set, `shaders` directory of the crate otherwise.
+    fn default() -> Self {
+        match std::env::var_os(Self::ENV_VAR) {
+            Some(base_dir) => Self::new(base_dir),
+            None => Self::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders")),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use gl::types::GLuint;
+
+use crate::{framebuffer, gl_assert_no_err};
+use crate::render_state::RenderState;
+
+use nalgebra_glm as glm;
+
+
+/// Depth map of the scene as seen from directional light.
+///
+/// Shadow pass draws casters with their depth only programs and light matrices in place of camera ones,
+/// see `Painter::shadow_caster` and `Painter::draw_shadow`, so model and instance transforms of their vertex
+/// shaders apply. Main shaders then sample `depth_texture` bound at `TEXTURE_UNIT` as `sampler2DShadow`
+/// at `light_space_matrix` transformed positions, `shaders/shadow.glsl` implements the lookup.
+pub struct ShadowMap {
+    framebuffer: GLuint,
+    depth: GLuint,
+    size: u32,
+    light_view: glm::Mat4,
+    light_projection: glm::Mat4,
+}
+
+impl ShadowMap {
+    pub const DEFAULT_SIZE: u32 = 2048;
+    pub const TEXTURE_UNIT: GLuint = 1;
+    /// Slope scaled depth offset applied during shadow pass against shadow acne.
+    const POLYGON_OFFSET: (f32, f32) = (2.0, 4.0);
+
+    pub fn new(size: u32) -> Self {
+        let mut framebuffer = 0;
+        let mut depth = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_2D, 1, &mut depth);
+            gl::TextureStorage2D(depth, 1, gl::DEPTH_COMPONENT32F, size as _, size as _);
+            gl::TextureParameteri(depth, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+            gl::TextureParameteri(depth, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+            // samples outside of the map compare against far plane so they are lit.
+            gl::TextureParameteri(depth, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as _);
+            gl::TextureParameteri(depth, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as _);
+            gl::TextureParameterfv(depth, gl::TEXTURE_BORDER_COLOR, [1.0f32; 4].as_ptr());
+            gl::TextureParameteri(depth, gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as _);
+            gl::TextureParameteri(depth, gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL as _);
+
+            gl::CreateFramebuffers(1, &mut framebuffer);
+            gl::NamedFramebufferTexture(framebuffer, gl::DEPTH_ATTACHMENT, depth, 0);
+            gl::NamedFramebufferDrawBuffer(framebuffer, gl::NONE);
+            gl::NamedFramebufferReadBuffer(framebuffer, gl::NONE);
+        }
+        gl_assert_no_err!();
+        let status = unsafe { gl::CheckNamedFramebufferStatus(framebuffer, gl::FRAMEBUFFER) };
+        assert_eq!(status, gl::FRAMEBUFFER_COMPLETE, "shadow map framebuffer {} is incomplete", framebuffer);
+        Self {
+            framebuffer,
+            depth,
+            size,
+            light_view: glm::identity(),
+            light_projection: glm::identity(),
+        }
+    }
+
+    /// Fit orthographic light frustum around sphere with given center and radius.
+    pub fn set_light(&mut self, direction: &glm::Vec3, center: &glm::Vec3, radius: f32) {
+        let direction = direction.normalize();
+        let up = if direction.cross(&glm::Vec3::y()).norm() < 1e-3 { glm::Vec3::z() } else { glm::Vec3::y() };
+        let eye = center - direction * radius * 2.0;
+        self.light_view = glm::look_at(&eye, center, &up);
+        self.light_projection = glm::ortho(-radius, radius, -radius, radius, radius, radius * 3.0);
+    }
+
+    pub fn light_view(&self) -> &glm::Mat4 {
+        &self.light_view
+    }
+
+    pub fn light_projection(&self) -> &glm::Mat4 {
+        &self.light_projection
+    }
+
+    /// World to light clip space matrix main shaders use to find shadow map texels.
+    pub fn light_space_matrix(&self) -> glm::Mat4 {
+        self.light_projection * self.light_view
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn depth_texture(&self) -> GLuint {
+        self.depth
+    }
+
+    pub fn bind_texture(&self, unit: GLuint) {
+        gl_assert_no_err!();
+        unsafe { gl::BindTextureUnit(unit, self.depth); }
+        gl_assert_no_err!();
+    }
+
+    /// Clear the map and run shadow pass `draw` rendering casters into it with given light view and projection.
+    pub fn render(&self, draw: impl FnOnce(&glm::Mat4, &glm::Mat4)) {
+        let _framebuffer_binder = framebuffer::ScopedBinder::new(self.framebuffer, (self.size, self.size));
+        gl_assert_no_err!();
+        unsafe { gl::Clear(gl::DEPTH_BUFFER_BIT); }
+        gl_assert_no_err!();
+        let (factor, units) = Self::POLYGON_OFFSET;
+        let _render_state_binder = RenderState::default().polygon_offset(factor, units).scoped_binder();
+        draw(&self.light_view, &self.light_projection);
+    }
+}
+
+impl Drop for ShadowMap {
+    fn drop(&mut self) {
+        log::debug!("Deleting shadow map framebuffer {}", self.framebuffer);
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteTextures(1, &self.depth);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{geometry, painter, program, texture, drawing, index_buffer, uniform, render_state};
+
+use crate::gl_assert_no_err;
+use painter::Painter;
+use program::ProgramError;
+use texture::Cubemap;
+use drawing::DrawMode;
+use index_buffer::IndexBufferObject;
+use uniform::NamedUniform;
+use render_state::{RenderState, DepthFunc};
+
+use nalgebra_glm as glm;
+
+
+/// Cube map drawn behind all other geometry.
+///
+/// Expects `perspective_matrix` and `view_matrix` uniforms, view matrix should be updated
+/// through `update_view` which removes camera translation so that skybox stays centered on the viewer.
+pub struct Skybox {
+    painter: Painter<IndexBufferObject<u8>>,
+    cubemap: Cubemap,
+}
+
+impl Skybox {
+    pub const TEXTURE_UNIT: gl::types::GLuint = 0;
+    pub const VIEW_MATRIX_ID: &'static str = "view_matrix";
+
+    pub fn new(cubemap: Cubemap, uniforms: impl Iterator<Item=NamedUniform>) -> Result<Self, ProgramError> {
+        gl_assert_no_err!();
+        unsafe { gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS); }
+        gl_assert_no_err!();
+        // skybox is rendered at depth 1.0 which fails the default GL_LESS test against a cleared depth buffer.
+        let painter = Painter::new(geometry::skybox(uniforms)?, DrawMode::Triangles)
+            .render_state(RenderState::default().depth_func(DepthFunc::LessEqual));
+        Ok(Self { painter, cubemap })
+    }
+
+    pub fn cubemap(&self) -> &Cubemap {
+        &self.cubemap
+    }
+
+    pub fn rotation_only(view: &glm::Mat4) -> glm::Mat4 {
+        glm::mat3_to_mat4(&glm::mat4_to_mat3(view))
+    }
+
+    pub fn update_view(&mut self, view: &glm::Mat4) {
+        let rotation = Self::rotation_only(view);
+        self.painter.binder_mut().update_uniform(Self::VIEW_MATRIX_ID, Box::new(*rotation.as_ref()));
+    }
+
+    pub fn draw(&self) {
+        self.cubemap.bind(Self::TEXTURE_UNIT);
+        self.painter.draw();
+    }
+}
+
+impl crate::Drawable for Skybox {
+    fn draw(&self) {
+        Skybox::draw(self)
+    }
+
+    fn set_camera(&mut self, view: &glm::Mat4, projection: &glm::Mat4) {
+        crate::Drawable::set_camera(&mut self.painter, &Self::rotation_only(view), projection);
+    }
+}
@@ -0,0 +1,144 @@
+use std::cell::Cell;
+use gl::types::GLuint;
+
+
+/// Shadow copy of opengl binding state used to skip redundant binds.
+///
+/// Cache is valid only as long as every bind goes through this module, code that calls
+/// `glBindVertexArray` / `glUseProgram` directly must call `invalidate` afterwards.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct StateCache {
+    pub vao: GLuint,
+    pub program: GLuint,
+}
+
+thread_local! {
+    static STATE: Cell<StateCache> = Cell::new(StateCache::default());
+}
+
+pub fn current() -> StateCache {
+    STATE.with(|state| state.get())
+}
+
+/// Forget cached bindings and reload them from opengl.
+pub fn invalidate() {
+    let mut vao = 0;
+    let mut program = 0;
+    unsafe {
+        gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, &mut vao);
+        gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut program);
+    }
+    STATE.with(|state| state.set(StateCache { vao: vao as _, program: program as _ }));
+}
+
+/// Bind vertex array unless it is already bound, returns whether gl call was issued.
+pub fn bind_vertex_array(id: GLuint) -> bool {
+    STATE.with(|state| {
+        let mut cache = state.get();
+        if cache.vao == id {
+            return false;
+        }
+        calls::bind_vertex_array(id);
+        cache.vao = id;
+        state.set(cache);
+        true
+    })
+}
+
+/// Record deletion of vertex array, opengl reverts binding to 0 when bound vertex array is deleted.
+pub fn forget_vertex_array(id: GLuint) {
+    STATE.with(|state| {
+        let mut cache = state.get();
+        if cache.vao == id {
+            cache.vao = 0;
+            state.set(cache);
+        }
+    })
+}
+
+/// Use program unless it is already in use, returns whether gl call was issued.
+pub fn use_program(id: GLuint) -> bool {
+    STATE.with(|state| {
+        let mut cache = state.get();
+        if cache.program == id {
+            return false;
+        }
+        calls::use_program(id);
+        cache.program = id;
+        state.set(cache);
+        true
+    })
+}
+
+/// Binding calls issued by the cache, tests record them instead of calling into opengl.
+#[cfg(not(test))]
+mod calls {
+    use gl::types::GLuint;
+    use crate::gl_assert_no_err;
+
+    pub fn bind_vertex_array(id: GLuint) {
+        gl_assert_no_err!();
+        unsafe { gl::BindVertexArray(id); }
+        gl_assert_no_err!();
+    }
+
+    pub fn use_program(id: GLuint) {
+        gl_assert_no_err!();
+        unsafe { gl::UseProgram(id); }
+        gl_assert_no_err!();
+    }
+}
+
+#[cfg(test)]
+pub mod calls {
+    use std::cell::RefCell;
+    use gl::types::GLuint;
+
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub enum Call {
+        BindVertexArray(GLuint),
+        UseProgram(GLuint),
+    }
+
+    thread_local! {
+        static ISSUED: RefCell<Vec<Call>> = RefCell::new(Vec::new());
+    }
+
+    pub fn bind_vertex_array(id: GLuint) {
+        ISSUED.with(|issued| issued.borrow_mut().push(Call::BindVertexArray(id)));
+    }
+
+    pub fn use_program(id: GLuint) {
+        ISSUED.with(|issued| issued.borrow_mut().push(Call::UseProgram(id)));
+    }
+
+    /// Calls issued on current thread since the last `take`.
+    pub fn take() -> Vec<Call> {
+        ISSUED.with(|issued| issued.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calls::Call;
+
+    #[test]
+    fn redundant_binds_issue_no_calls() {
+        assert!(use_program(3));
+        assert!(!use_program(3));
+        assert!(bind_vertex_array(5));
+        assert!(!bind_vertex_array(5));
+        assert!(use_program(4));
+        assert_eq!(calls::take(), [Call::UseProgram(3), Call::BindVertexArray(5), Call::UseProgram(4)]);
+    }
+
+    #[test]
+    fn forgotten_vertex_array_is_bound_again() {
+        bind_vertex_array(5);
+        forget_vertex_array(5);
+        assert_eq!(current().vao, 0);
+        bind_vertex_array(5);
+        assert_eq!(calls::take(), [Call::BindVertexArray(5), Call::BindVertexArray(5)]);
+    }
+}
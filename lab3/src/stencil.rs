@@ -0,0 +1,232 @@
+use gl::types::{GLenum, GLint, GLuint};
+use crate::gl_assert_no_err;
+
+
+/// Opengl stencil comparison function enumeration.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StencilFunc {
+    Never,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Always,
+}
+
+impl StencilFunc {
+    /// Get opengl defined numerical value of given variant
+    pub fn get(&self) -> GLenum {
+        match *self {
+            StencilFunc::Never => gl::NEVER,
+            StencilFunc::Less => gl::LESS,
+            StencilFunc::LessEqual => gl::LEQUAL,
+            StencilFunc::Greater => gl::GREATER,
+            StencilFunc::GreaterEqual => gl::GEQUAL,
+            StencilFunc::Equal => gl::EQUAL,
+            StencilFunc::NotEqual => gl::NOTEQUAL,
+            StencilFunc::Always => gl::ALWAYS,
+        }
+    }
+
+    /// Variant of opengl defined numerical value, `None` for values that are not comparison functions.
+    pub fn from_gl(func: GLenum) -> Option<Self> {
+        match func {
+            gl::NEVER => Some(StencilFunc::Never),
+            gl::LESS => Some(StencilFunc::Less),
+            gl::LEQUAL => Some(StencilFunc::LessEqual),
+            gl::GREATER => Some(StencilFunc::Greater),
+            gl::GEQUAL => Some(StencilFunc::GreaterEqual),
+            gl::EQUAL => Some(StencilFunc::Equal),
+            gl::NOTEQUAL => Some(StencilFunc::NotEqual),
+            gl::ALWAYS => Some(StencilFunc::Always),
+            _ => None,
+        }
+    }
+}
+
+/// Opengl stencil buffer update enumeration.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    Increment,
+    IncrementWrap,
+    Decrement,
+    DecrementWrap,
+    Invert,
+}
+
+impl StencilOp {
+    /// Get opengl defined numerical value of given variant
+    pub fn get(&self) -> GLenum {
+        match *self {
+            StencilOp::Keep => gl::KEEP,
+            StencilOp::Zero => gl::ZERO,
+            StencilOp::Replace => gl::REPLACE,
+            StencilOp::Increment => gl::INCR,
+            StencilOp::IncrementWrap => gl::INCR_WRAP,
+            StencilOp::Decrement => gl::DECR,
+            StencilOp::DecrementWrap => gl::DECR_WRAP,
+            StencilOp::Invert => gl::INVERT,
+        }
+    }
+
+    /// Variant of opengl defined numerical value, `None` for values that are not stencil operations.
+    pub fn from_gl(op: GLenum) -> Option<Self> {
+        match op {
+            gl::KEEP => Some(StencilOp::Keep),
+            gl::ZERO => Some(StencilOp::Zero),
+            gl::REPLACE => Some(StencilOp::Replace),
+            gl::INCR => Some(StencilOp::Increment),
+            gl::INCR_WRAP => Some(StencilOp::IncrementWrap),
+            gl::DECR => Some(StencilOp::Decrement),
+            gl::DECR_WRAP => Some(StencilOp::DecrementWrap),
+            gl::INVERT => Some(StencilOp::Invert),
+            _ => None,
+        }
+    }
+}
+
+/// Stencil test configuration, wraps `glStencilFunc`, `glStencilOp` and `glStencilMask`.
+///
+/// Selection outline is drawn in two passes:
+/// ```ignore
+/// let object = painter.stencil(StencilState::write(1));
+/// object.draw();
+/// object.draw_outline(&scaled_up_flat_color_program);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct StencilState {
+    pub func: StencilFunc,
+    pub reference: GLint,
+    pub read_mask: GLuint,
+    pub stencil_fail: StencilOp,
+    pub depth_fail: StencilOp,
+    pub depth_pass: StencilOp,
+    pub write_mask: GLuint,
+}
+
+impl Default for StencilState {
+    /// Opengl's initial state - test always passes and buffer is left untouched.
+    fn default() -> Self {
+        Self {
+            func: StencilFunc::Always,
+            reference: 0,
+            read_mask: !0,
+            stencil_fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            depth_pass: StencilOp::Keep,
+            write_mask: !0,
+        }
+    }
+}
+
+impl StencilState {
+    /// Write `reference` wherever fragment is drawn.
+    pub fn write(reference: GLint) -> Self {
+        Self {
+            func: StencilFunc::Always,
+            reference,
+            depth_pass: StencilOp::Replace,
+            ..Default::default()
+        }
+    }
+
+    /// Draw only where stencil differs from `reference`, buffer is left untouched.
+    pub fn not_equal(reference: GLint) -> Self {
+        Self {
+            func: StencilFunc::NotEqual,
+            reference,
+            write_mask: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Draw only where stencil equals `reference`, buffer is left untouched.
+    pub fn equal(reference: GLint) -> Self {
+        Self {
+            func: StencilFunc::Equal,
+            reference,
+            write_mask: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Front face stencil state of current context.
+    pub fn current() -> Self {
+        let mut values = [0; 7];
+        let pnames = [
+            gl::STENCIL_FUNC, gl::STENCIL_REF, gl::STENCIL_VALUE_MASK,
+            gl::STENCIL_FAIL, gl::STENCIL_PASS_DEPTH_FAIL, gl::STENCIL_PASS_DEPTH_PASS,
+            gl::STENCIL_WRITEMASK,
+        ];
+        gl_assert_no_err!();
+        for (value, pname) in values.iter_mut().zip(pnames) {
+            unsafe { gl::GetIntegerv(pname, value); }
+        }
+        gl_assert_no_err!();
+        let [func, reference, read_mask, stencil_fail, depth_fail, depth_pass, write_mask] = values;
+        let op = |op: GLint| StencilOp::from_gl(op as _).expect("context reports valid stencil operation");
+        Self {
+            func: StencilFunc::from_gl(func as _).expect("context reports valid stencil function"),
+            reference,
+            read_mask: read_mask as _,
+            stencil_fail: op(stencil_fail),
+            depth_fail: op(depth_fail),
+            depth_pass: op(depth_pass),
+            write_mask: write_mask as _,
+        }
+    }
+
+    fn apply(&self) {
+        gl_assert_no_err!();
+        unsafe {
+            gl::StencilFunc(self.func.get(), self.reference, self.read_mask);
+            gl::StencilOp(self.stencil_fail.get(), self.depth_fail.get(), self.depth_pass.get());
+            gl::StencilMask(self.write_mask);
+        }
+        gl_assert_no_err!();
+    }
+
+    pub fn scoped_binder(&self) -> ScopedBinder {
+        ScopedBinder::new(*self)
+    }
+}
+
+/// Enables stencil test with given state, restores previous state and stencil test on drop.
+pub struct ScopedBinder {
+    state: StencilState,
+    previous: StencilState,
+    was_enabled: bool,
+}
+
+impl ScopedBinder {
+    pub fn new(state: StencilState) -> Self {
+        log::debug!("Enabling stencil test with {:?}", state);
+        let previous = StencilState::current();
+        gl_assert_no_err!();
+        let was_enabled = unsafe {
+            let was_enabled = gl::IsEnabled(gl::STENCIL_TEST) == gl::TRUE;
+            gl::Enable(gl::STENCIL_TEST);
+            was_enabled
+        };
+        gl_assert_no_err!();
+        state.apply();
+        Self { state, previous, was_enabled }
+    }
+}
+
+impl Drop for ScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring stencil {:?} after {:?}", self.previous, self.state);
+        self.previous.apply();
+        if !self.was_enabled {
+            gl_assert_no_err!();
+            unsafe { gl::Disable(gl::STENCIL_TEST); }
+            gl_assert_no_err!();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::marker::PhantomData;
+
+use gl::types::GLuint;
+
+use crate::gl_assert_no_err;
+
+
+/// Immutable size shader storage buffer holding `len` values of plain data type `T`.
+///
+/// `T` must match std430 layout of the block it is bound to, e.g. `[f32; 4]` for `vec4`
+/// (`vec3` arrays are padded to 16 bytes in std430 too).
+pub struct ShaderStorageBuffer<T: Copy> {
+    id: GLuint,
+    len: usize,
+    _data: PhantomData<T>,
+}
+
+impl<T: Copy> ShaderStorageBuffer<T> {
+    /// Zero initialized buffer with room for `len` values.
+    pub fn new(len: usize) -> Self {
+        Self::with_data(len, std::ptr::null())
+    }
+
+    pub fn from_slice(data: &[T]) -> Self {
+        Self::with_data(data.len(), data.as_ptr() as *const std::ffi::c_void)
+    }
+
+    fn with_data(len: usize, data: *const std::ffi::c_void) -> Self {
+        let mut id = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateBuffers(1, &mut id);
+            gl::NamedBufferStorage(id, Self::byte_count(len) as _, data, gl::DYNAMIC_STORAGE_BIT);
+            if data.is_null() {
+                gl::ClearNamedBufferData(id, gl::R8UI, gl::RED_INTEGER, gl::UNSIGNED_BYTE, std::ptr::null());
+            }
+        }
+        gl_assert_no_err!();
+        Self { id, len, _data: PhantomData }
+    }
+
+    fn byte_count(len: usize) -> usize {
+        len * std::mem::size_of::<T>()
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Overwrite beginning of the buffer, storage can not grow so `data` must fit.
+    pub fn write(&self, data: &[T]) {
+        assert!(data.len() <= self.len, "writing {} values into storage buffer of {}", data.len(), self.len);
+        gl_assert_no_err!();
+        unsafe {
+            gl::NamedBufferSubData(self.id, 0, Self::byte_count(data.len()) as _, data.as_ptr() as *const std::ffi::c_void);
+        }
+        gl_assert_no_err!();
+    }
+
+    /// Copy whole buffer back to client memory, stalls until gpu finishes writing it.
+    pub fn read(&self) -> Vec<T> {
+        let mut data = Vec::<T>::with_capacity(self.len);
+        gl_assert_no_err!();
+        unsafe {
+            gl::GetNamedBufferSubData(self.id, 0, Self::byte_count(self.len) as _, data.as_mut_ptr() as *mut std::ffi::c_void);
+            data.set_len(self.len);
+        }
+        gl_assert_no_err!();
+        data
+    }
+
+    /// Expose buffer to `layout(std430, binding = binding) buffer` block.
+    pub fn bind(&self, binding: GLuint) {
+        log::debug!("Binding storage buffer {} to binding {}", self.id, binding);
+        gl_assert_no_err!();
+        unsafe { gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, self.id); }
+        gl_assert_no_err!();
+    }
+}
+
+impl<T: Copy> Drop for ShaderStorageBuffer<T> {
+    fn drop(&mut self) {
+        if self.id == 0 {
+            return;
+        }
+        log::debug!("Deleting storage buffer {}", self.id);
+        unsafe { gl::DeleteBuffers(1, &self.id); }
+    }
+}
@@ -0,0 +1,224 @@
+use std::cell::RefCell;
+
+use crate::{binder, vertex, programs, index_buffer, drawing, painter, texture, viewport};
+
+use crate::gl_assert_no_err;
+use index_buffer::IndexBufferObject;
+use vertex::{VertexAttribute, BufferObject};
+use binder::Binder;
+use drawing::DrawMode;
+use painter::Painter;
+use texture::Texture2D;
+use viewport::Viewport;
+
+use nalgebra_glm as glm;
+
+
+/// Screen space quad of single glyph, positions are in pixels with origin in the top left corner.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GlyphQuad {
+    pub min: glm::Vec2,
+    pub max: glm::Vec2,
+    pub uv_min: glm::Vec2,
+    pub uv_max: glm::Vec2,
+}
+
+impl GlyphQuad {
+    /// Two triangles of `(x, y, u, v)` vertices.
+    fn vertices(&self) -> [VertexAttribute<f32, 4>; 6] {
+        let top_left = (self.min.x, self.min.y, self.uv_min.x, self.uv_min.y);
+        let top_right = (self.max.x, self.min.y, self.uv_max.x, self.uv_min.y);
+        let bottom_left = (self.min.x, self.max.y, self.uv_min.x, self.uv_max.y);
+        let bottom_right = (self.max.x, self.max.y, self.uv_max.x, self.uv_max.y);
+        [top_left, bottom_left, bottom_right, top_left, bottom_right, top_right].map(VertexAttribute::from)
+    }
+}
+
+/// Monospace bitmap font drawn as one textured quad per glyph on top of the frame.
+///
+/// Atlas is a grid of equally sized cells holding consecutive ascii characters starting at `FIRST_CHARACTER`
+/// row by row from the top left, glyph coverage is read from atlas alpha.
+pub struct TextRenderer {
+    atlas: Texture2D,
+    grid: (u32, u32),
+    painter: RefCell<Painter<IndexBufferObject>>,
+}
+
+impl TextRenderer {
+    pub const FIRST_CHARACTER: u8 = b' ';
+    pub const FALLBACK_CHARACTER: char = '?';
+    pub const TEXTURE_UNIT: gl::types::GLuint = 0;
+    pub const PROJECTION_MATRIX_ID: &'static str = "projection_matrix";
+    pub const TEXT_COLOR_ID: &'static str = "text_color";
+
+    /// `grid` is number of atlas cells in `(columns, rows)`.
+    pub fn new(atlas: Texture2D, grid: (u32, u32)) -> Self {
+        assert!(grid.0 > 0 && grid.1 > 0, "font atlas grid must not be empty");
+        let mut binder = Binder::builder()
+            .add_named_vbo("glyph_vertex", BufferObject::<f32, 4>::create(Box::new([])))
+            .program(programs::text())
+            .add_uniform(Self::PROJECTION_MATRIX_ID, Box::new(*glm::Mat4::identity().as_ref()))
+            .add_uniform(Self::TEXT_COLOR_ID, Box::new([1.0f32, 1.0, 1.0]))
+            .build();
+        binder.upload();
+        let painter = RefCell::new(Painter::new(binder, DrawMode::Triangles));
+        Self { atlas, grid, painter }
+    }
+
+    pub fn atlas(&self) -> &Texture2D {
+        &self.atlas
+    }
+
+    /// Size of single glyph in pixels when drawn with scale 1.
+    pub fn glyph_size(&self) -> glm::Vec2 {
+        let (width, height) = self.atlas.size();
+        glm::vec2(width as f32 / self.grid.0 as f32, height as f32 / self.grid.1 as f32)
+    }
+
+    /// Quads of `text` with top left corner of the first glyph at `(x, y)`.
+    ///
+    /// Whitespace advances the pen without producing quads and `\n` starts a new line.
+    pub fn layout(&self, text: &str, x: f32, y: f32, scale: f32) -> Vec<GlyphQuad> {
+        layout(self.grid, self.glyph_size() * scale, text, x, y)
+    }
+
+    /// Draw text over the current viewport, `(x, y)` are in pixels from its top left corner.
+    pub fn draw_text(&self, text: &str, x: f32, y: f32, scale: f32) {
+        self.draw_colored_text(text, x, y, scale, glm::vec3(1.0, 1.0, 1.0));
+    }
+
+    pub fn draw_colored_text(&self, text: &str, x: f32, y: f32, scale: f32, color: glm::Vec3) {
+        let quads = self.layout(text, x, y, scale);
+        if quads.is_empty() {
+            return;
+        }
+        let vertices = quads.iter()
+            .flat_map(GlyphQuad::vertices)
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let viewport = Viewport::current();
+        let projection = glm::ortho(0.0, viewport.width as f32, viewport.height as f32, 0.0, -1.0, 1.0);
+
+        let mut painter = self.painter.borrow_mut();
+        let binder = painter.binder_mut();
+        binder.swap_vbo(0, Box::new(BufferObject::create(vertices)));
+        binder.update_uniform(Self::PROJECTION_MATRIX_ID, Box::new(*projection.as_ref()));
+        binder.update_uniform(Self::TEXT_COLOR_ID, Box::new(*color.as_ref()));
+
+        let _overlay_binder = OverlayScopedBinder::new();
+        self.atlas.bind(Self::TEXTURE_UNIT);
+        painter.draw();
+    }
+}
+
+/// Atlas cell of `character`, characters missing from the atlas use `TextRenderer::FALLBACK_CHARACTER`.
+fn glyph_cell(grid: (u32, u32), character: char) -> u32 {
+    let cell_count = grid.0 * grid.1;
+    let cell = |character: char| (character as u32).checked_sub(TextRenderer::FIRST_CHARACTER as u32)
+        .filter(|cell| *cell < cell_count);
+    cell(character)
+        .or_else(|| cell(TextRenderer::FALLBACK_CHARACTER))
+        .unwrap_or(0)
+}
+
+/// See `TextRenderer::layout`, separate from the renderer so that it does not need the atlas texture.
+fn layout(grid: (u32, u32), glyph_size: glm::Vec2, text: &str, x: f32, y: f32) -> Vec<GlyphQuad> {
+    let cell_uv = glm::vec2(1.0 / grid.0 as f32, 1.0 / grid.1 as f32);
+    let mut pen = glm::vec2(x, y);
+    let mut quads = Vec::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\n' => {
+                pen = glm::vec2(x, pen.y + glyph_size.y);
+                continue;
+            }
+            character if character.is_whitespace() => (),
+            character => {
+                let cell = glyph_cell(grid, character);
+                let uv_min = glm::vec2((cell % grid.0) as f32, (cell / grid.0) as f32).component_mul(&cell_uv);
+                quads.push(GlyphQuad { min: pen, max: pen + glyph_size, uv_min, uv_max: uv_min + cell_uv });
+            }
+        }
+        pen.x += glyph_size.x;
+    }
+    quads
+}
+
+/// Alpha blending without depth test so that overlay is drawn over the whole frame.
+struct OverlayScopedBinder {
+    depth_test: bool,
+    blend: bool,
+    /// Previous `(src rgb, dst rgb, src alpha, dst alpha)` blend factors.
+    blend_func: [gl::types::GLint; 4],
+}
+
+impl OverlayScopedBinder {
+    fn new() -> Self {
+        gl_assert_no_err!();
+        let binder = unsafe {
+            let mut blend_func = [0; 4];
+            for (factor, pname) in blend_func.iter_mut()
+                .zip([gl::BLEND_SRC_RGB, gl::BLEND_DST_RGB, gl::BLEND_SRC_ALPHA, gl::BLEND_DST_ALPHA])
+            {
+                gl::GetIntegerv(pname, factor);
+            }
+            let binder = Self {
+                depth_test: gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE,
+                blend: gl::IsEnabled(gl::BLEND) == gl::TRUE,
+                blend_func,
+            };
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            binder
+        };
+        gl_assert_no_err!();
+        binder
+    }
+}
+
+impl Drop for OverlayScopedBinder {
+    fn drop(&mut self) {
+        gl_assert_no_err!();
+        unsafe {
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if !self.blend {
+                gl::Disable(gl::BLEND);
+            }
+            let [src_rgb, dst_rgb, src_alpha, dst_alpha] = self.blend_func.map(|factor| factor as gl::types::GLenum);
+            gl::BlendFuncSeparate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+        }
+        gl_assert_no_err!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: (u32, u32) = (16, 6);
+
+    #[test]
+    fn layout_produces_quad_per_visible_character() {
+        let quads = layout(GRID, glm::vec2(8.0, 16.0), "fps: 60\nok", 0.0, 0.0);
+        assert_eq!(quads.len(), 8);
+    }
+
+    #[test]
+    fn layout_advances_pen_over_whitespace_and_lines() {
+        let quads = layout(GRID, glm::vec2(8.0, 16.0), "a b\nc", 10.0, 20.0);
+        let corners = quads.iter().map(|quad| quad.min).collect::<Vec<_>>();
+        assert_eq!(corners, [glm::vec2(10.0, 20.0), glm::vec2(26.0, 20.0), glm::vec2(10.0, 36.0)]);
+        assert!(quads.iter().all(|quad| quad.max - quad.min == glm::vec2(8.0, 16.0)));
+    }
+
+    #[test]
+    fn characters_outside_atlas_use_fallback_cell() {
+        let fallback = glyph_cell(GRID, TextRenderer::FALLBACK_CHARACTER);
+        assert_eq!(glyph_cell(GRID, 'ł'), fallback);
+        assert_eq!(glyph_cell(GRID, '\u{7}'), fallback);
+        assert_eq!(glyph_cell(GRID, '!'), 1);
+    }
+}
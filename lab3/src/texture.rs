@@ -0,0 +1,427 @@
+use gl::types::{GLenum, GLfloat, GLuint};
+use crate::{context, gl_assert_no_err};
+
+use image::RgbaImage;
+
+
+/// Interpretation of stored 8 bit color values.
+///
+/// `Srgb` textures are decoded to linear space on sampling, color images (albedo, skyboxes) should use it
+/// while data textures (normal maps, roughness) are already `Linear`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorSpace {
+    Linear,
+    Srgb,
+}
+
+impl ColorSpace {
+    /// Internal format of rgba texture with 8 bits per channel.
+    pub fn rgba8_format(&self) -> GLenum {
+        match *self {
+            ColorSpace::Linear => gl::RGBA8,
+            ColorSpace::Srgb => gl::SRGB8_ALPHA8,
+        }
+    }
+}
+
+impl Default for ColorSpace {
+    fn default() -> Self { ColorSpace::Srgb }
+}
+
+/// Sampling parameters of `Texture2D`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextureParams {
+    pub min_filter: GLenum,
+    pub mag_filter: GLenum,
+    pub wrap_s: GLenum,
+    pub wrap_t: GLenum,
+    /// Maximal anisotropy, values above 1 require `GL_EXT_texture_filter_anisotropic` and are clamped
+    /// to the maximum supported by the driver.
+    pub anisotropy: f32,
+    /// Allocate and generate full mip chain, required by mipmap minification filters.
+    pub mipmaps: bool,
+}
+
+impl TextureParams {
+    pub const ANISOTROPIC_FILTERING_EXTENSION: &'static str = "GL_EXT_texture_filter_anisotropic";
+    // not part of gl 4.5 bindings, values are shared by the extension and gl 4.6 core.
+    const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+    const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+    pub fn with_anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    /// Linear filtering without mip chain, e.g. for textures that are never minified.
+    pub fn without_mipmaps(mut self) -> Self {
+        self.mipmaps = false;
+        self.min_filter = gl::LINEAR;
+        self
+    }
+
+    pub fn uses_mipmaps(&self) -> bool {
+        matches!(
+            self.min_filter,
+            gl::NEAREST_MIPMAP_NEAREST | gl::NEAREST_MIPMAP_LINEAR | gl::LINEAR_MIPMAP_NEAREST | gl::LINEAR_MIPMAP_LINEAR
+        )
+    }
+
+    /// Highest anisotropy supported by current context, 1 if anisotropic filtering is unavailable.
+    pub fn max_anisotropy() -> f32 {
+        if !context::has_extension(Self::ANISOTROPIC_FILTERING_EXTENSION) {
+            return 1.0;
+        }
+        let mut max: GLfloat = 1.0;
+        gl_assert_no_err!();
+        unsafe { gl::GetFloatv(Self::MAX_TEXTURE_MAX_ANISOTROPY, &mut max); }
+        gl_assert_no_err!();
+        max
+    }
+
+    fn apply(&self, texture: GLuint) {
+        assert!(
+            self.mipmaps || !self.uses_mipmaps(),
+            "mipmap minification filter 0x{:X} requires mipmaps", self.min_filter
+        );
+        let parameters: [(GLenum, GLenum); 4] = [
+            (gl::TEXTURE_MIN_FILTER, self.min_filter),
+            (gl::TEXTURE_MAG_FILTER, self.mag_filter),
+            (gl::TEXTURE_WRAP_S, self.wrap_s),
+            (gl::TEXTURE_WRAP_T, self.wrap_t),
+        ];
+        gl_assert_no_err!();
+        for (parameter, value) in parameters {
+            unsafe { gl::TextureParameteri(texture, parameter, value as _); }
+        }
+        gl_assert_no_err!();
+        if self.anisotropy > 1.0 {
+            let anisotropy = self.anisotropy.min(Self::max_anisotropy());
+            if anisotropy < self.anisotropy {
+                log::warn!("Anisotropy {} is not supported, using {}", self.anisotropy, anisotropy);
+            }
+            if anisotropy > 1.0 {
+                unsafe { gl::TextureParameterf(texture, Self::TEXTURE_MAX_ANISOTROPY, anisotropy); }
+            }
+        }
+        gl_assert_no_err!();
+    }
+}
+
+impl Default for TextureParams {
+    fn default() -> Self {
+        Self {
+            min_filter: gl::LINEAR_MIPMAP_LINEAR,
+            mag_filter: gl::LINEAR,
+            wrap_s: gl::REPEAT,
+            wrap_t: gl::REPEAT,
+            anisotropy: 1.0,
+            mipmaps: true,
+        }
+    }
+}
+
+/// Two dimensional rgba texture, with full mip chain unless disabled by its `TextureParams`.
+pub struct Texture2D {
+    id: GLuint,
+    size: (u32, u32),
+    color_space: ColorSpace,
+    params: TextureParams,
+}
+
+impl Texture2D {
+    pub fn from_file(path: &std::path::Path, color_space: ColorSpace) -> Result<Self, image::ImageError> {
+        Self::from_file_with_params(path, color_space, TextureParams::default())
+    }
+
+    pub fn from_file_with_params(
+        path: &std::path::Path,
+        color_space: ColorSpace,
+        params: TextureParams,
+    ) -> Result<Self, image::ImageError> {
+        Ok(Self::from_image_with_params(&image::open(path)?.into_rgba8(), color_space, params))
+    }
+
+    pub fn from_image(image: &RgbaImage, color_space: ColorSpace) -> Self {
+        Self::from_image_with_params(image, color_space, TextureParams::default())
+    }
+
+    pub fn from_image_with_params(image: &RgbaImage, color_space: ColorSpace, params: TextureParams) -> Self {
+        let size = image.dimensions();
+        let levels = if params.mipmaps { 32 - size.0.max(size.1).max(1).leading_zeros() } else { 1 };
+
+        let mut id = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+            gl::TextureStorage2D(id, levels as _, color_space.rgba8_format(), size.0 as _, size.1 as _);
+            gl::TextureSubImage2D(
+                id,
+                0,
+                0, 0,
+                size.0 as _, size.1 as _,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                image.as_raw().as_ptr() as *const std::ffi::c_void,
+            );
+            if params.mipmaps {
+                gl::GenerateTextureMipmap(id);
+            }
+        }
+        gl_assert_no_err!();
+        params.apply(id);
+        Self { id, size, color_space, params }
+    }
+
+    /// Magenta and black checkerboard standing in for textures that are not loaded yet.
+    pub fn placeholder() -> Self {
+        const MAGENTA: image::Rgba<u8> = image::Rgba([255, 0, 255, 255]);
+        const BLACK: image::Rgba<u8> = image::Rgba([0, 0, 0, 255]);
+        let image = RgbaImage::from_fn(2, 2, |x, y| if (x + y) % 2 == 0 { MAGENTA } else { BLACK });
+        let params = TextureParams { min_filter: gl::NEAREST, mag_filter: gl::NEAREST, ..TextureParams::default().without_mipmaps() };
+        Self::from_image_with_params(&image, ColorSpace::Srgb, params)
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    pub fn params(&self) -> &TextureParams {
+        &self.params
+    }
+
+    /// Anisotropy applied by the driver, read back from the texture.
+    pub fn anisotropy(&self) -> f32 {
+        if !context::has_extension(TextureParams::ANISOTROPIC_FILTERING_EXTENSION) {
+            return 1.0;
+        }
+        let mut anisotropy: GLfloat = 1.0;
+        gl_assert_no_err!();
+        unsafe { gl::GetTextureParameterfv(self.id, TextureParams::TEXTURE_MAX_ANISOTROPY, &mut anisotropy); }
+        gl_assert_no_err!();
+        anisotropy
+    }
+
+    pub fn bind(&self, unit: GLuint) {
+        gl_assert_no_err!();
+        unsafe { gl::BindTextureUnit(unit, self.id); }
+        gl_assert_no_err!();
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        log::debug!("Deleting texture {}", self.id);
+        unsafe { gl::DeleteTextures(1, &self.id); }
+    }
+}
+
+/// Stack of equally sized rgba layers, sampled in shaders through `sampler2DArray` with `vec3(uv, layer)`.
+pub struct Texture2DArray {
+    id: GLuint,
+    size: (u32, u32),
+    layers: u32,
+    color_space: ColorSpace,
+    params: TextureParams,
+}
+
+impl Texture2DArray {
+    /// Allocate storage for given number of layers, contents are undefined until uploaded with `upload_layer`.
+    pub fn new(size: (u32, u32), layers: u32, color_space: ColorSpace, params: TextureParams) -> Self {
+        assert!(layers > 0, "texture array requires at least one layer");
+        let levels = if params.mipmaps { 32 - size.0.max(size.1).max(1).leading_zeros() } else { 1 };
+
+        let mut id = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_2D_ARRAY, 1, &mut id);
+            gl::TextureStorage3D(id, levels as _, color_space.rgba8_format(), size.0 as _, size.1 as _, layers as _);
+        }
+        gl_assert_no_err!();
+        params.apply(id);
+        Self { id, size, layers, color_space, params }
+    }
+
+    pub fn from_files(paths: &[&std::path::Path], color_space: ColorSpace, params: TextureParams) -> Result<Self, image::ImageError> {
+        let mut images = Vec::with_capacity(paths.len());
+        for path in paths {
+            images.push(image::open(path)?.into_rgba8());
+        }
+        Ok(Self::from_images(&images, color_space, params))
+    }
+
+    /// Texture with one layer per image, in order.
+    pub fn from_images(images: &[RgbaImage], color_space: ColorSpace, params: TextureParams) -> Self {
+        assert!(!images.is_empty(), "texture array requires at least one layer");
+        let mut texture = Self::new(images[0].dimensions(), images.len() as _, color_space, params);
+        for (layer, image) in images.iter().enumerate() {
+            texture.write_layer(layer as _, image);
+        }
+        texture.generate_mipmaps();
+        texture
+    }
+
+    /// Replace contents of single layer, mip chain is regenerated.
+    pub fn upload_layer(&mut self, layer: u32, image: &RgbaImage) {
+        self.write_layer(layer, image);
+        self.generate_mipmaps();
+    }
+
+    fn write_layer(&mut self, layer: u32, image: &RgbaImage) {
+        assert!(layer < self.layers, "layer {} is out of bounds of texture array with {} layers", layer, self.layers);
+        assert_eq!(image.dimensions(), self.size, "all texture array layers must have the same dimensions");
+        gl_assert_no_err!();
+        unsafe {
+            gl::TextureSubImage3D(
+                self.id,
+                0,
+                0, 0, layer as _,
+                self.size.0 as _, self.size.1 as _, 1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                image.as_raw().as_ptr() as *const std::ffi::c_void,
+            );
+        }
+        gl_assert_no_err!();
+    }
+
+    fn generate_mipmaps(&self) {
+        if self.params.mipmaps {
+            gl_assert_no_err!();
+            unsafe { gl::GenerateTextureMipmap(self.id); }
+            gl_assert_no_err!();
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Layer count as reported by the driver for base level.
+    pub fn depth(&self) -> u32 {
+        let mut depth = 0;
+        gl_assert_no_err!();
+        unsafe { gl::GetTextureLevelParameteriv(self.id, 0, gl::TEXTURE_DEPTH, &mut depth); }
+        gl_assert_no_err!();
+        depth as _
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    pub fn params(&self) -> &TextureParams {
+        &self.params
+    }
+
+    pub fn bind(&self, unit: GLuint) {
+        gl_assert_no_err!();
+        unsafe { gl::BindTextureUnit(unit, self.id); }
+        gl_assert_no_err!();
+    }
+}
+
+impl Drop for Texture2DArray {
+    fn drop(&mut self) {
+        log::debug!("Deleting texture array {}", self.id);
+        unsafe { gl::DeleteTextures(1, &self.id); }
+    }
+}
+
+/// Cube map texture, faces are ordered as in opengl: +X, -X, +Y, -Y, +Z, -Z.
+pub struct Cubemap {
+    id: GLuint,
+    size: (u32, u32),
+}
+
+impl Cubemap {
+    pub const FACE_COUNT: usize = 6;
+
+    pub fn from_files(faces: [&std::path::Path; Self::FACE_COUNT], color_space: ColorSpace) -> Result<Self, image::ImageError> {
+        let mut images = Vec::with_capacity(Self::FACE_COUNT);
+        for face in faces {
+            images.push(image::open(face)?.into_rgba8());
+        }
+        Ok(Self::from_images(&images, color_space))
+    }
+
+    pub fn from_images(faces: &[RgbaImage], color_space: ColorSpace) -> Self {
+        assert_eq!(faces.len(), Self::FACE_COUNT, "cube map requires exactly {} faces", Self::FACE_COUNT);
+        let size = faces[0].dimensions();
+        assert!(
+            faces.iter().all(|face| face.dimensions() == size),
+            "all cube map faces must have the same dimensions"
+        );
+
+        let mut id = 0;
+        gl_assert_no_err!();
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_CUBE_MAP, 1, &mut id);
+            gl::TextureStorage2D(id, 1, color_space.rgba8_format(), size.0 as _, size.1 as _);
+        }
+        gl_assert_no_err!();
+        for (face_index, face) in faces.iter().enumerate() {
+            unsafe {
+                gl::TextureSubImage3D(
+                    id,
+                    0,
+                    0, 0, face_index as _,
+                    size.0 as _, size.1 as _, 1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    face.as_raw().as_ptr() as *const std::ffi::c_void,
+                );
+            }
+            gl_assert_no_err!();
+        }
+        let parameters: [(GLenum, GLenum); 5] = [
+            (gl::TEXTURE_MIN_FILTER, gl::LINEAR),
+            (gl::TEXTURE_MAG_FILTER, gl::LINEAR),
+            (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE),
+            (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE),
+            (gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE),
+        ];
+        for (parameter, value) in parameters {
+            unsafe { gl::TextureParameteri(id, parameter, value as _); }
+        }
+        gl_assert_no_err!();
+        Self { id, size }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn bind(&self, unit: GLuint) {
+        gl_assert_no_err!();
+        unsafe { gl::BindTextureUnit(unit, self.id); }
+        gl_assert_no_err!();
+    }
+}
+
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        log::debug!("Deleting cube map {}", self.id);
+        unsafe { gl::DeleteTextures(1, &self.id); }
+    }
+}
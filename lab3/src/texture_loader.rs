@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::JoinHandle;
+
+use crate::texture::{ColorSpace, Texture2D};
+
+use image::RgbaImage;
+
+
+type Decoded = Result<RgbaImage, image::ImageError>;
+
+struct Request {
+    path: PathBuf,
+    reply: Sender<Decoded>,
+}
+
+/// Decodes images on a background thread so that loading textures does not stall the window.
+///
+/// Only decoding happens on the worker, textures are created on the render thread by `StreamedTexture::poll`
+/// since gl context is current there only.
+pub struct TextureLoader {
+    requests: Option<Sender<Request>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl TextureLoader {
+    pub fn new() -> Self {
+        let (requests, pending) = mpsc::channel::<Request>();
+        let worker = std::thread::Builder::new()
+            .name("texture loader".to_owned())
+            .spawn(move || {
+                for request in pending {
+                    log::debug!("Decoding texture {}", request.path.display());
+                    let decoded = image::open(&request.path).map(|image| image.into_rgba8());
+                    // texture may have been dropped before its image was decoded.
+                    let _ = request.reply.send(decoded);
+                }
+            })
+            .expect("failed to spawn texture loader thread");
+        Self { requests: Some(requests), worker: Some(worker) }
+    }
+
+    /// Queue image for decoding, returned texture shows a placeholder until the image is uploaded.
+    pub fn load(&self, path: impl Into<PathBuf>, color_space: ColorSpace) -> StreamedTexture {
+        let path = path.into();
+        let (reply, pending) = mpsc::channel();
+        self.requests.as_ref()
+            .expect("requests are closed only on drop")
+            .send(Request { path: path.clone(), reply })
+            .expect("texture loader thread terminated");
+        StreamedTexture { path, color_space, pending: Some(pending), texture: Texture2D::placeholder(), loaded: false }
+    }
+}
+
+impl Default for TextureLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TextureLoader {
+    fn drop(&mut self) {
+        // closing the channel ends worker's loop once queued images are decoded.
+        drop(self.requests.take());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Texture whose image is being decoded by `TextureLoader`.
+pub struct StreamedTexture {
+    path: PathBuf,
+    color_space: ColorSpace,
+    pending: Option<Receiver<Decoded>>,
+    texture: Texture2D,
+    loaded: bool,
+}
+
+impl StreamedTexture {
+    /// Upload decoded image if it is ready, returns whether texture holds the loaded image.
+    ///
+    /// Must be called on the render thread. After a decoding error the placeholder is kept.
+    pub fn poll(&mut self) -> Result<bool, image::ImageError> {
+        let pending = match self.pending {
+            Some(ref pending) => pending,
+            None => return Ok(self.loaded),
+        };
+        let decoded = match pending.try_recv() {
+            Ok(decoded) => decoded,
+            Err(TryRecvError::Empty) => return Ok(false),
+            Err(TryRecvError::Disconnected) => panic!("texture loader thread terminated before decoding {}", self.path.display()),
+        };
+        self.pending = None;
+        self.texture = Texture2D::from_image(&decoded?, self.color_space);
+        self.loaded = true;
+        log::debug!("Uploaded texture {} as {}", self.path.display(), self.texture.id());
+        Ok(true)
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Loaded texture or placeholder.
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    pub fn bind(&self, unit: gl::types::GLuint) {
+        self.texture.bind(unit)
+    }
+}
@@ -0,0 +1,97 @@
+use gl::types::{GLenum, GLuint};
+
+use crate::drawing::DrawMode;
+use crate::gl_assert_no_err;
+
+
+/// Transform feedback object recording vertex shader outputs into buffers.
+///
+/// Captured varyings are chosen when linking, see `Program::from_shaders_with_varyings`.
+pub struct TransformFeedback {
+    id: GLuint,
+}
+
+impl TransformFeedback {
+    pub fn create() -> Self {
+        let mut id = 0;
+        gl_assert_no_err!();
+        unsafe { gl::CreateTransformFeedbacks(1, &mut id); }
+        gl_assert_no_err!();
+        Self { id }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Capture into given buffer, `index` is the varying index for separate attributes and 0 for interleaved ones.
+    pub fn set_buffer(&self, index: GLuint, buffer_id: GLuint) {
+        log::debug!("Attaching buffer {} to transform feedback {} at index {}", buffer_id, self.id, index);
+        gl_assert_no_err!();
+        unsafe { gl::TransformFeedbackBufferBase(self.id, index, buffer_id); }
+        gl_assert_no_err!();
+    }
+
+    /// Record primitives drawn while returned binder lives, program with registered varyings must be in use.
+    ///
+    /// With `discard` set primitives are not rasterized, e.g. when only updating particle state.
+    pub fn capture(&self, mode: DrawMode, discard: bool) -> ScopedCapture {
+        ScopedCapture::new(self.id, Self::primitive_mode(mode), discard)
+    }
+
+    /// Transform feedback records only independent points, lines or triangles.
+    fn primitive_mode(mode: DrawMode) -> GLenum {
+        match mode {
+            DrawMode::Points => gl::POINTS,
+            DrawMode::Lines | DrawMode::LineStrip | DrawMode::LineLoop => gl::LINES,
+            DrawMode::Triangles | DrawMode::TriangleStrip | DrawMode::TriangleFan => gl::TRIANGLES,
+        }
+    }
+}
+
+impl Drop for TransformFeedback {
+    fn drop(&mut self) {
+        if self.id == 0 {
+            return;
+        }
+        log::debug!("Deleting transform feedback {}", self.id);
+        unsafe { gl::DeleteTransformFeedbacks(1, &self.id); }
+    }
+}
+
+/// Active transform feedback, ends capture on drop.
+pub struct ScopedCapture {
+    id: GLuint,
+    discard: bool,
+}
+
+impl ScopedCapture {
+    fn new(id: GLuint, primitive_mode: GLenum, discard: bool) -> Self {
+        log::debug!("Beginning transform feedback {}", id);
+        gl_assert_no_err!();
+        unsafe {
+            if discard {
+                gl::Enable(gl::RASTERIZER_DISCARD);
+            }
+            gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, id);
+            gl::BeginTransformFeedback(primitive_mode);
+        }
+        gl_assert_no_err!();
+        Self { id, discard }
+    }
+}
+
+impl Drop for ScopedCapture {
+    fn drop(&mut self) {
+        log::debug!("Ending transform feedback {}", self.id);
+        gl_assert_no_err!();
+        unsafe {
+            gl::EndTransformFeedback();
+            gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, 0);
+            if self.discard {
+                gl::Disable(gl::RASTERIZER_DISCARD);
+            }
+        }
+        gl_assert_no_err!();
+    }
+}
@@ -1,14 +1,20 @@
 use std::fmt::{Debug, Display, Formatter};
+use std::time::Instant;
 use gl::types::GLint;
 use crate::{gl_assert_no_err};
+use crate::program::Program;
+
+use nalgebra_glm as glm;
 
 pub type NamedUniform = (&'static str, Box<dyn TypedUniform>);
 
 pub enum UniformType {
+    Bool,
     Float,
     Vec2,
     Vec3,
     Mat4,
+    Struct(&'static str),
 }
 
 pub fn to_owned<U, T, I>(uniforms: I) -> impl Iterator<Item=(&'static str, Box<dyn TypedUniform>)>
@@ -27,13 +33,29 @@ pub trait UniformTypeProvider {
     fn uniform_type(&self) -> UniformType;
 }
 
+impl UniformType {
+    /// Opengl type enum reported by introspection, `None` for structs which are reported per member.
+    pub fn gl_type(&self) -> Option<gl::types::GLenum> {
+        match self {
+            UniformType::Bool => Some(gl::BOOL),
+            UniformType::Float => Some(gl::FLOAT),
+            UniformType::Vec2 => Some(gl::FLOAT_VEC2),
+            UniformType::Vec3 => Some(gl::FLOAT_VEC3),
+            UniformType::Mat4 => Some(gl::FLOAT_MAT4),
+            UniformType::Struct(_) => None,
+        }
+    }
+}
+
 impl Display for UniformType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let r#type = match self {
+            UniformType::Bool => "bool",
             UniformType::Float => "float",
             UniformType::Vec3 => "vec3",
             UniformType::Vec2 => "vec2",
-            UniformType::Mat4 => "mat4"
+            UniformType::Mat4 => "mat4",
+            UniformType::Struct(name) => name,
         };
         write!(f, "uniform {}", r#type)?;
         Ok(())
@@ -42,6 +64,9 @@ impl Display for UniformType {
 
 pub trait Uniform: Debug {
     fn bind(&self, location: GLint);
+
+    /// Uniforms whose value changes on its own (e.g. time) are rebound before every draw.
+    fn is_time_varying(&self) -> bool { false }
 }
 
 pub trait TypedUniform: Uniform + UniformTypeProvider { }
@@ -62,6 +87,20 @@ impl UniformTypeProvider for f32 {
     }
 }
 
+impl Uniform for bool {
+    fn bind(&self, location: GLint) {
+        gl_assert_no_err!();
+        unsafe { crate::gl::Uniform1i(location, *self as _); }
+        gl_assert_no_err!();
+    }
+}
+
+impl UniformTypeProvider for bool {
+    fn uniform_type(&self) -> UniformType {
+        UniformType::Bool
+    }
+}
+
 impl UniformTypeProvider for [f32; 3] {
     fn uniform_type(&self) -> UniformType {
         UniformType::Vec3
@@ -165,3 +204,358 @@ impl Uniform for [f32; 16] {
         gl_assert_no_err!();
     }
 }
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LightType {
+    Directional,
+    Point,
+}
+
+impl LightType {
+    /// Value of `kind` member as seen by the shader.
+    pub fn get(&self) -> GLint {
+        match *self {
+            LightType::Directional => 0,
+            LightType::Point => 1,
+        }
+    }
+}
+
+/// Single light source.
+///
+/// For directional lights `position` holds the direction light is travelling in.
+#[derive(Debug, Copy, Clone)]
+pub struct Light {
+    pub light_type: LightType,
+    pub position: glm::Vec3,
+    pub color: glm::Vec3,
+    pub intensity: f32,
+}
+
+impl Light {
+    pub fn directional(direction: glm::Vec3, color: glm::Vec3, intensity: f32) -> Self {
+        Self { light_type: LightType::Directional, position: direction, color, intensity }
+    }
+
+    pub fn point(position: glm::Vec3, color: glm::Vec3, intensity: f32) -> Self {
+        Self { light_type: LightType::Point, position, color, intensity }
+    }
+}
+
+/// Owner of lights uploaded as uniform array.
+///
+/// Matches following glsl declarations:
+/// ```glsl
+/// struct Light {
+///     int kind;
+///     vec3 position;
+///     vec3 color;
+///     float intensity;
+/// };
+///
+/// uniform Light lights[MAX_LIGHTS];
+/// uniform int light_count;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Lighting {
+    lights: Vec<Light>,
+}
+
+impl Lighting {
+    pub const MAX_LIGHTS: usize = 8;
+    pub const LIGHTS_ID: &'static str = "lights";
+    pub const LIGHT_COUNT_ID: &'static str = "light_count";
+
+    pub fn new(lights: &[Light]) -> Self {
+        assert!(lights.len() <= Self::MAX_LIGHTS, "at most {} lights are supported", Self::MAX_LIGHTS);
+        Self { lights: lights.to_vec() }
+    }
+
+    pub fn add(&mut self, light: Light) {
+        assert!(self.lights.len() < Self::MAX_LIGHTS, "at most {} lights are supported", Self::MAX_LIGHTS);
+        self.lights.push(light);
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn lights_mut(&mut self) -> &mut [Light] {
+        &mut self.lights
+    }
+
+    /// Name of given member of light with given index e.g. `lights[0].color`.
+    pub fn member_ident(index: usize, member: &str) -> String {
+        format!("{}[{}].{}", Self::LIGHTS_ID, index, member)
+    }
+
+    /// Upload all lights and their count into given program.
+    /// Members that are not active in the program are skipped.
+    pub fn upload(&self, program: &Program) {
+        let _program_binder = program.scoped_binder();
+        let location = |index, member| program.uniform_location(&Self::member_ident(index, member));
+
+        if let Some(count_location) = program.uniform_location(Self::LIGHT_COUNT_ID) {
+            gl_assert_no_err!();
+            unsafe { crate::gl::Uniform1i(count_location, self.lights.len() as _); }
+            gl_assert_no_err!();
+        }
+        for (index, light) in self.lights.iter().enumerate() {
+            if let Some(kind_location) = location(index, "kind") {
+                gl_assert_no_err!();
+                unsafe { crate::gl::Uniform1i(kind_location, light.light_type.get()); }
+                gl_assert_no_err!();
+            }
+            if let Some(position_location) = location(index, "position") {
+                light.position.as_ref().bind(position_location);
+            }
+            if let Some(color_location) = location(index, "color") {
+                light.color.as_ref().bind(color_location);
+            }
+            if let Some(intensity_location) = location(index, "intensity") {
+                light.intensity.bind(intensity_location);
+            }
+        }
+    }
+}
+
+/// Phong lighting coefficients of a surface.
+///
+/// Uploaded as glsl struct declared with explicit location, its members occupy consecutive locations:
+/// ```glsl
+/// struct Material {
+///     vec3 ambient;
+///     vec3 diffuse;
+///     vec3 specular;
+///     float shininess;
+/// };
+///
+/// layout(location = N) uniform Material material;
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Material {
+    pub ambient: glm::Vec3,
+    pub diffuse: glm::Vec3,
+    pub specular: glm::Vec3,
+    pub shininess: f32,
+}
+
+impl Material {
+    pub const IDENT: &'static str = "material";
+    pub const MEMBER_IDENTS: [&'static str; 4] = ["ambient", "diffuse", "specular", "shininess"];
+
+    pub fn new(ambient: glm::Vec3, diffuse: glm::Vec3, specular: glm::Vec3, shininess: f32) -> Self {
+        Self { ambient, diffuse, specular, shininess }
+    }
+
+    /// Name of given member e.g. `material.diffuse`.
+    pub fn member_ident(member: &str) -> String {
+        format!("{}.{}", Self::IDENT, member)
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(
+            glm::vec3(0.1, 0.1, 0.1),
+            glm::vec3(0.5, 0.5, 0.5),
+            glm::vec3(0.5, 0.5, 0.5),
+            32.0,
+        )
+    }
+}
+
+impl Uniform for Material {
+    fn bind(&self, location: GLint) {
+        self.ambient.as_ref().bind(location);
+        self.diffuse.as_ref().bind(location + 1);
+        self.specular.as_ref().bind(location + 2);
+        self.shininess.bind(location + 3);
+    }
+}
+
+impl UniformTypeProvider for Material {
+    fn uniform_type(&self) -> UniformType {
+        UniformType::Struct("Material")
+    }
+}
+
+/// 4x4 matrix uniform, uploaded in column major order.
+#[derive(Debug, Copy, Clone)]
+pub struct Mat4Uniform(pub glm::Mat4);
+
+/// 3 component vector uniform.
+#[derive(Debug, Copy, Clone)]
+pub struct Vec3Uniform(pub glm::Vec3);
+
+/// Scalar float uniform.
+#[derive(Debug, Copy, Clone)]
+pub struct F32Uniform(pub f32);
+
+impl From<glm::Mat4> for Mat4Uniform {
+    fn from(matrix: glm::Mat4) -> Self { Self(matrix) }
+}
+
+impl From<glm::Vec3> for Vec3Uniform {
+    fn from(vector: glm::Vec3) -> Self { Self(vector) }
+}
+
+impl From<f32> for F32Uniform {
+    fn from(value: f32) -> Self { Self(value) }
+}
+
+impl Uniform for Mat4Uniform {
+    fn bind(&self, location: GLint) {
+        gl_assert_no_err!();
+        unsafe { crate::gl::UniformMatrix4fv(location, 1, gl::FALSE, self.0.as_ptr()); }
+        gl_assert_no_err!();
+    }
+}
+
+impl Uniform for Vec3Uniform {
+    fn bind(&self, location: GLint) {
+        self.0.as_ref().bind(location);
+    }
+}
+
+impl Uniform for F32Uniform {
+    fn bind(&self, location: GLint) {
+        self.0.bind(location);
+    }
+}
+
+impl UniformTypeProvider for Mat4Uniform {
+    fn uniform_type(&self) -> UniformType {
+        UniformType::Mat4
+    }
+}
+
+impl UniformTypeProvider for Vec3Uniform {
+    fn uniform_type(&self) -> UniformType {
+        UniformType::Vec3
+    }
+}
+
+impl UniformTypeProvider for F32Uniform {
+    fn uniform_type(&self) -> UniformType {
+        UniformType::Float
+    }
+}
+
+/// Seconds elapsed since creation as `float` uniform, value is sampled whenever uniform is bound.
+#[derive(Debug, Copy, Clone)]
+pub struct TimeUniform {
+    start: Instant,
+}
+
+impl TimeUniform {
+    pub const IDENT: &'static str = "time";
+
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    pub fn starting_at(start: Instant) -> Self {
+        Self { start }
+    }
+
+    pub fn elapsed_at(&self, now: Instant) -> f32 {
+        now.saturating_duration_since(self.start).as_secs_f32()
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed_at(Instant::now())
+    }
+}
+
+impl Default for TimeUniform {
+    fn default() -> Self { Self::new() }
+}
+
+/// Directional light spinning around an axis over time, e.g. moving sun.
+///
+/// Binds as direction vec3 and is rebound before every draw like `TimeUniform`.
+#[derive(Debug, Copy, Clone)]
+pub struct RotatingLight {
+    light: Light,
+    axis: glm::Vec3,
+    /// Radians per second.
+    angular_speed: f32,
+    time: TimeUniform,
+}
+
+impl RotatingLight {
+    pub fn new(light: Light, axis: glm::Vec3, angular_speed: f32) -> Self {
+        Self::starting_at(light, axis, angular_speed, Instant::now())
+    }
+
+    pub fn starting_at(light: Light, axis: glm::Vec3, angular_speed: f32, start: Instant) -> Self {
+        assert!(light.light_type == LightType::Directional, "only directional lights can be rotated");
+        Self { light, axis: axis.normalize(), angular_speed, time: TimeUniform::starting_at(start) }
+    }
+
+    /// Light with its direction rotated by angle accumulated until `now`.
+    pub fn light_at(&self, now: Instant) -> Light {
+        let angle = self.angular_speed * self.time.elapsed_at(now);
+        Light { position: glm::rotate_vec3(&self.light.position, angle, &self.axis), ..self.light }
+    }
+
+    pub fn direction_at(&self, now: Instant) -> glm::Vec3 {
+        self.light_at(now).position
+    }
+}
+
+impl Uniform for RotatingLight {
+    fn bind(&self, location: GLint) {
+        self.direction_at(Instant::now()).as_ref().bind(location);
+    }
+
+    fn is_time_varying(&self) -> bool { true }
+}
+
+impl UniformTypeProvider for RotatingLight {
+    fn uniform_type(&self) -> UniformType {
+        UniformType::Vec3
+    }
+}
+
+impl Uniform for TimeUniform {
+    fn bind(&self, location: GLint) {
+        self.elapsed().bind(location);
+    }
+
+    fn is_time_varying(&self) -> bool { true }
+}
+
+impl UniformTypeProvider for TimeUniform {
+    fn uniform_type(&self) -> UniformType {
+        UniformType::Float
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sun(start: Instant) -> RotatingLight {
+        let light = Light::directional(glm::vec3(1.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 1.0), 1.0);
+        RotatingLight::starting_at(light, glm::vec3(0.0, 2.0, 0.0), std::f32::consts::FRAC_PI_2, start)
+    }
+
+    #[test]
+    fn rotating_light_starts_at_initial_direction() {
+        let start = Instant::now();
+        assert_eq!(sun(start).direction_at(start), glm::vec3(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn rotating_light_turns_by_angular_speed_around_axis() {
+        let start = Instant::now();
+        let direction = sun(start).direction_at(start + Duration::from_secs(1));
+        assert!((direction - glm::vec3(0.0, 0.0, -1.0)).norm() < 1e-5, "{}", direction);
+        let direction = sun(start).direction_at(start + Duration::from_secs(4));
+        assert!((direction - glm::vec3(1.0, 0.0, 0.0)).norm() < 1e-5, "{}", direction);
+    }
+}
@@ -1,7 +1,7 @@
 use gl::types::GLuint;
-use crate::vertex::AttributeType;
+use crate::vertex::{AttributeType, PointerKind};
 
-use crate::gl_assert_no_err;
+use crate::{gl_assert_no_err, state_cache};
 
 pub struct ArrayObject {
     id: GLuint
@@ -18,58 +18,123 @@ impl ArrayObject {
         Self { id }
     }
 
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
     pub fn scoped_binder(&self) -> ScopedBinder {
         ScopedBinder::new(self.id)
     }
 
-    pub fn set_attrib_divisor(&self, layout: usize, divisor: usize) {
-        log::debug!("Setting attribute divisor for layout(location = {}) to {}", layout, divisor);
+    /// Attach element buffer to the vao without binding either of them.
+    pub fn set_element_buffer(&self, buffer_id: GLuint) {
+        log::debug!("Attaching element buffer {} to vao {}", buffer_id, self.id);
         gl_assert_no_err!();
         unsafe {
-            gl::VertexAttribDivisor(layout as _, divisor as _);
+            gl::VertexArrayElementBuffer(self.id, buffer_id);
         }
         gl_assert_no_err!();
     }
 
-    pub fn set_vertex_attrib_pointer(&self, layout: usize, attr: &AttributeType) {
-        log::debug!("Setting attribute pointer, layout(location = {})", layout);
-        log::debug!("\tAttribute type: {:?}", attr);
+    /// Source vertices of given binding point from buffer, format of attributes reading it is unaffected.
+    pub fn set_vertex_buffer(&self, binding: usize, buffer_id: GLuint, attr: &AttributeType) {
+        log::debug!("Attaching buffer {} to binding {} of vao {}", buffer_id, binding, self.id);
         gl_assert_no_err!();
         unsafe {
-            gl::EnableVertexAttribArray(layout as _);
+            gl::VertexArrayVertexBuffer(self.id, binding as _, buffer_id, 0, attr.size_bytes() as _);
         }
         gl_assert_no_err!();
+    }
+
+    /// Make attributes sourced from given binding point advance once per `divisor` instances.
+    pub fn set_binding_divisor(&self, binding: usize, divisor: usize) {
+        log::debug!("Setting divisor of binding {} to {}", binding, divisor);
+        gl_assert_no_err!();
+        unsafe {
+            gl::VertexArrayBindingDivisor(self.id, binding as _, divisor as _);
+        }
+        gl_assert_no_err!();
+    }
+
+    /// Describe attribute occupying locations starting at `layout` and read it from given binding point.
+    pub fn set_attrib_format(&self, layout: usize, attr: &AttributeType, binding: usize) {
+        log::debug!("Setting attribute format, layout(location = {}), binding {}", layout, binding);
+        log::debug!("\tAttribute type: {:?}", attr);
+        for column in 0..attr.location_count() {
+            let location = (layout + column) as _;
+            let relative_offset = (column * attr.location_component_count() * attr.byte_size()) as _;
+            let size = attr.location_component_count() as _;
+            gl_assert_no_err!();
+            unsafe {
+                gl::EnableVertexArrayAttrib(self.id, location);
+                match attr.pointer_kind() {
+                    PointerKind::Float => gl::VertexArrayAttribFormat(
+                        self.id, location, size, attr.gl_type(), gl::FALSE, relative_offset,
+                    ),
+                    PointerKind::Integer => gl::VertexArrayAttribIFormat(
+                        self.id, location, size, attr.gl_type(), relative_offset,
+                    ),
+                    PointerKind::Double => gl::VertexArrayAttribLFormat(
+                        self.id, location, size, attr.gl_type(), relative_offset,
+                    ),
+                }
+                gl::VertexArrayAttribBinding(self.id, location, binding as _);
+            }
+            gl_assert_no_err!();
+        }
+    }
+
+    /// Query whether attribute at given location is enabled, together with binding point it reads from.
+    pub fn attrib_binding(&self, location: usize) -> Option<usize> {
+        let mut enabled = 0;
+        let mut binding = 0;
+        // `glGetVertexArrayIndexediv` does not report attribute binding, it is queried from the bound vao instead.
+        let _vao_binder = self.scoped_binder();
+        gl_assert_no_err!();
         unsafe {
-            gl::VertexAttribPointer(
-                layout as _,
-                attr.component_count() as _,
-                attr.gl_type(),
-                gl::FALSE,
-                0,
-                std::ptr::null(),
-            );
+            gl::GetVertexAttribiv(location as _, gl::VERTEX_ATTRIB_ARRAY_ENABLED, &mut enabled);
+            gl::GetVertexAttribiv(location as _, gl::VERTEX_ATTRIB_BINDING, &mut binding);
         }
         gl_assert_no_err!();
+        (enabled != 0).then(|| binding as usize)
     }
 }
 
-pub struct ScopedBinder(GLuint);
+impl Drop for ArrayObject {
+    fn drop(&mut self) {
+        if self.id == 0 {
+            return;
+        }
+        log::debug!("Deleting vao {}", self.id);
+        gl_assert_no_err!();
+        unsafe { gl::DeleteVertexArrays(1, &self.id); }
+        gl_assert_no_err!();
+        state_cache::forget_vertex_array(self.id);
+    }
+}
+
+/// Binds vao and restores previously bound one on drop so that scopes can nest.
+///
+/// Outermost scope leaves its vao bound so that consecutive draws of the same vao skip `glBindVertexArray`.
+pub struct ScopedBinder {
+    id: GLuint,
+    previous: GLuint,
+}
 
 impl ScopedBinder {
     pub fn new(vao_id: GLuint) -> Self {
         log::debug!("Binding vao {}", vao_id);
-        gl_assert_no_err!();
-        unsafe { gl::BindVertexArray(vao_id); }
-        gl_assert_no_err!();
-        Self(vao_id)
+        let previous = state_cache::current().vao;
+        state_cache::bind_vertex_array(vao_id);
+        Self { id: vao_id, previous }
     }
 }
 
 impl Drop for ScopedBinder {
     fn drop(&mut self) {
-        log::debug!("Unbinding vao {}", self.0);
-        gl_assert_no_err!();
-        unsafe { gl::BindVertexArray(0); }
-        gl_assert_no_err!();
+        if self.previous != 0 {
+            log::debug!("Unbinding vao {}, restoring {}", self.id, self.previous);
+            state_cache::bind_vertex_array(self.previous);
+        }
     }
-}
\ No newline at end of file
+}
@@ -98,7 +98,18 @@ impl<P: Primitive, const COMPONENT_COUNT: usize> AsRef<[P]> for VertexAttribute<
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Determines which `glVertexAttrib*Pointer` variant must be used for given attribute.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PointerKind {
+    /// Attribute read as (possibly converted) float - `glVertexAttribPointer`.
+    Float,
+    /// Attribute read as int / uint - `glVertexAttribIPointer`.
+    Integer,
+    /// Attribute read as double - `glVertexAttribLPointer`.
+    Double,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct AttributeType {
     component_count: usize,
     byte_size: usize,
@@ -110,14 +121,47 @@ impl AttributeType {
         self.component_count
     }
 
+    /// Size of single component in bytes.
     pub fn byte_size(&self) -> usize {
         self.byte_size
     }
 
-    pub fn gl_type(&self) -> GLenum {
+    /// Opengl type of single component.
+    pub fn component_type(&self) -> GLenum {
         self.gl_type_enum
     }
 
+    pub fn gl_type(&self) -> GLenum {
+        self.component_type()
+    }
+
+    /// Size of whole attribute in bytes.
+    pub fn size_bytes(&self) -> usize {
+        self.component_count * self.byte_size
+    }
+
+    /// Number of consecutive attribute locations occupied, matrices take one location per column.
+    pub fn location_count(&self) -> usize {
+        match self.component_count {
+            9 => 3,
+            16 => 4,
+            _ => 1,
+        }
+    }
+
+    /// Number of components read from each occupied location.
+    pub fn location_component_count(&self) -> usize {
+        self.component_count / self.location_count()
+    }
+
+    pub fn pointer_kind(&self) -> PointerKind {
+        match self.gl_type_enum {
+            FLOAT => PointerKind::Float,
+            DOUBLE => PointerKind::Double,
+            _ => PointerKind::Integer,
+        }
+    }
+
     pub const fn from_attribute_params<P: Primitive, const COMPONENT_COUNT: usize>() -> Self {
         Self {
             component_count: COMPONENT_COUNT,
@@ -5,6 +5,8 @@ use gl::types::GLuint;
 use crate::{gl_assert, gl_assert_no_err};
 use crate::index_buffer::{GlBufferTargetProvider};
 
+/// Creates `BufferObject` from tuples / arrays of literals, primitive type and component count are inferred.
+/// Nested arrays (`[[f32; 4]; 4]`) produce matrix attributes occupying one location per column.
 #[macro_export]
 macro_rules! attributes {
     () => {
@@ -25,8 +27,14 @@ pub trait Buffer {
 
     fn attribute_type(&self) -> AttributeType;
 
+    /// Number of whole attributes stored in the buffer (not number of components).
     fn vertex_count(&self) -> usize;
 
+    /// Number of instances that share single attribute, 0 for per vertex attributes.
+    fn divisor(&self) -> usize { 0 }
+
+    fn is_instanced(&self) -> bool { self.divisor() != 0 }
+
     fn scoped_binder(&self) -> ScopedBinder {
         gl_assert_no_err!();
         ScopedBinder::new(self.id())
@@ -68,7 +76,7 @@ impl<P: Primitive, const N: usize> Buffer for BufferObject<P, N> {
         let byte_count = self.vertex_count() * std::mem::size_of::<VertexAttribute<P, N>>();
         let raw_ptr = self.buffer.as_ref().as_ptr() as *const std::ffi::c_void;
         unsafe {
-            gl::BufferData(<Self as GlBufferTargetProvider>::TARGET, byte_count as _, raw_ptr, gl::STATIC_DRAW);
+            gl::NamedBufferData(self.id, byte_count as _, raw_ptr, gl::STATIC_DRAW);
         }
         gl_assert_no_err!();
     }
@@ -82,12 +90,16 @@ impl<P: Primitive, const N: usize> Buffer for BufferObject<P, N> {
     }
 
     fn vertex_count(&self) -> usize {
+        // elements are whole attributes already - no division by component count needed.
         self.buffer.len()
     }
 }
 
 impl<P: Primitive, const N: usize> Drop for BufferObject<P, N> {
     fn drop(&mut self) {
+        if self.id == 0 {
+            return;
+        }
         log::debug!("Deleting buffer object {}", self.id);
         unsafe {
             gl::DeleteBuffers(1, &self.id)
@@ -96,27 +108,75 @@ impl<P: Primitive, const N: usize> Drop for BufferObject<P, N> {
 }
 //endregion
 
+//region InstancedBuffer
+/// Buffer whose attributes advance per instance rather than per vertex.
+#[derive(Debug)]
+pub struct InstancedBuffer<B: Buffer> {
+    buffer: B,
+    divisor: usize,
+}
+
+impl<B: Buffer> InstancedBuffer<B> {
+    pub fn new(buffer: B, divisor: usize) -> Self {
+        assert!(divisor > 0, "instanced buffer requires non zero divisor");
+        Self { buffer, divisor }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.buffer
+    }
+}
+
+impl<B: Buffer> Buffer for InstancedBuffer<B> {
+    fn upload(&self) {
+        self.buffer.upload()
+    }
+
+    fn id(&self) -> GLuint {
+        self.buffer.id()
+    }
+
+    fn attribute_type(&self) -> AttributeType {
+        self.buffer.attribute_type()
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.buffer.vertex_count()
+    }
+
+    fn divisor(&self) -> usize {
+        self.divisor
+    }
+}
+//endregion
+
 //region ScopedBinder
-pub struct ScopedBinder(GLuint);
+/// Binds buffer to `GL_ARRAY_BUFFER` and restores previously bound one on drop so that scopes can nest.
+pub struct ScopedBinder {
+    id: GLuint,
+    previous: GLuint,
+}
 
 impl ScopedBinder {
     pub fn new(buffer_id: GLuint) -> Self {
         log::debug!("Binding buffer object {}", buffer_id);
+        let mut previous = 0;
         gl_assert_no_err!();
         unsafe {
+            gl::GetIntegerv(gl::ARRAY_BUFFER_BINDING, &mut previous);
             gl::BindBuffer(gl::ARRAY_BUFFER, buffer_id);
         }
         gl_assert_no_err!();
-        Self(buffer_id)
+        Self { id: buffer_id, previous: previous as _ }
     }
 }
 
 impl Drop for ScopedBinder {
     fn drop(&mut self) {
-        log::debug!("Unbinding buffer object {}", self.0);
+        log::debug!("Unbinding buffer object {}, restoring {}", self.id, self.previous);
         gl_assert_no_err!();
         unsafe {
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.previous);
         }
         gl_assert_no_err!();
     }
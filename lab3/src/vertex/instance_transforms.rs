@@ -0,0 +1,48 @@
+use gl::types::GLuint;
+
+use super::{Buffer, BufferObject, InstancedBuffer, VertexAttribute, AttributeType};
+
+use nalgebra_glm as glm;
+
+
+/// Per instance model matrices, fed to `layout(location = N) in mat4` which occupies locations N..N+4.
+///
+/// Matrices are stored column major so every location receives one column, all columns advance once per instance.
+pub struct InstanceTransforms {
+    buffer: InstancedBuffer<BufferObject<f32, 16>>,
+}
+
+impl InstanceTransforms {
+    pub fn new(transforms: &[glm::Mat4]) -> Self {
+        let attributes = transforms.iter()
+            .map(|transform| {
+                let mut columns = [0.0f32; 16];
+                columns.copy_from_slice(transform.as_slice());
+                VertexAttribute::from(columns)
+            })
+            .collect::<Vec<_>>();
+        Self { buffer: InstancedBuffer::new(BufferObject::create(attributes.into_boxed_slice()), 1) }
+    }
+}
+
+impl Buffer for InstanceTransforms {
+    fn upload(&self) {
+        self.buffer.upload()
+    }
+
+    fn id(&self) -> GLuint {
+        self.buffer.id()
+    }
+
+    fn attribute_type(&self) -> AttributeType {
+        self.buffer.attribute_type()
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.buffer.vertex_count()
+    }
+
+    fn divisor(&self) -> usize {
+        self.buffer.divisor()
+    }
+}
@@ -0,0 +1,78 @@
+use super::{Buffer, Primitive, VertexAttribute, AttributeType};
+use gl;
+use gl::types::GLuint;
+
+use crate::gl_assert_no_err;
+
+
+/// Vertex buffer persistently and coherently mapped into client memory, for data rewritten every frame.
+///
+/// Size is fixed at creation. Write only regions previous frames no longer draw from, e.g. triple buffer
+/// the data and pick the region with base instance.
+pub struct MappedBuffer<P: Primitive, const N: usize> {
+    id: GLuint,
+    ptr: *mut VertexAttribute<P, N>,
+    len: usize,
+}
+
+impl<P: Primitive, const N: usize> MappedBuffer<P, N> {
+    const FLAGS: gl::types::GLbitfield = gl::MAP_READ_BIT | gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+
+    pub fn create(initial: Box<[VertexAttribute<P, N>]>) -> Self {
+        let len = initial.len();
+        let byte_count = len * std::mem::size_of::<VertexAttribute<P, N>>();
+        let mut id = 0;
+        gl_assert_no_err!();
+        let ptr = unsafe {
+            gl::CreateBuffers(1, &mut id);
+            gl::NamedBufferStorage(
+                id,
+                byte_count as _,
+                initial.as_ptr() as *const std::ffi::c_void,
+                Self::FLAGS | gl::DYNAMIC_STORAGE_BIT,
+            );
+            gl::MapNamedBufferRange(id, 0, byte_count as _, Self::FLAGS)
+        };
+        gl_assert_no_err!();
+        assert!(!ptr.is_null(), "failed to map buffer {}", id);
+        Self { id, ptr: ptr as *mut VertexAttribute<P, N>, len }
+    }
+
+    /// Readable view of buffer contents, mapping is readable so this does not go through write-only memory.
+    pub fn as_slice(&self) -> &[VertexAttribute<P, N>] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Writable view of buffer contents, writes are visible to subsequent draw calls.
+    pub fn as_mut_slice(&mut self) -> &mut [VertexAttribute<P, N>] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<P: Primitive, const N: usize> Buffer for MappedBuffer<P, N> {
+    fn upload(&self) {
+        // storage is allocated and filled on creation, afterwards data is written through the mapping.
+    }
+
+    fn id(&self) -> GLuint {
+        self.id
+    }
+
+    fn attribute_type(&self) -> AttributeType {
+        VertexAttribute::<P, N>::attribute_type()
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.len
+    }
+}
+
+impl<P: Primitive, const N: usize> Drop for MappedBuffer<P, N> {
+    fn drop(&mut self) {
+        log::debug!("Deleting mapped buffer object {}", self.id);
+        unsafe {
+            gl::UnmapNamedBuffer(self.id);
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
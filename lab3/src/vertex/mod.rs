@@ -1,8 +1,11 @@
 mod attribute;
 mod buffer;
+pub mod mapped_buffer;
+mod instance_transforms;
 
 pub mod array_object;
-pub use buffer::{Buffer, BufferObject};
-pub use attribute::{Primitive, VertexAttribute, AttributeType};
+pub use buffer::{Buffer, BufferObject, InstancedBuffer};
+pub use instance_transforms::InstanceTransforms;
+pub use attribute::{Primitive, VertexAttribute, AttributeType, PointerKind};
 
 pub use array_object::ArrayObject;
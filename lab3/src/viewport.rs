@@ -0,0 +1,80 @@
+use gl::types::GLint;
+use crate::gl_assert_no_err;
+
+
+/// Window region mapped from ndc by `glViewport`, origin in bottom-left corner.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Viewport {
+    pub x: GLint,
+    pub y: GLint,
+    pub width: GLint,
+    pub height: GLint,
+}
+
+impl Viewport {
+    pub fn new(x: GLint, y: GLint, width: GLint, height: GLint) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Query currently set viewport.
+    pub fn current() -> Self {
+        let mut viewport = [0; 4];
+        gl_assert_no_err!();
+        unsafe { gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()); }
+        gl_assert_no_err!();
+        let [x, y, width, height] = viewport;
+        Self { x, y, width, height }
+    }
+
+    /// Aspect ratio projections rendered into this viewport should use.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+
+    /// Split into left and right half.
+    pub fn split_horizontally(&self) -> (Viewport, Viewport) {
+        let left_width = self.width / 2;
+        (
+            Self::new(self.x, self.y, left_width, self.height),
+            Self::new(self.x + left_width, self.y, self.width - left_width, self.height),
+        )
+    }
+
+    pub fn scoped_binder(&self) -> ScopedBinder {
+        ScopedBinder::new(*self)
+    }
+}
+
+/// Sets viewport and restores previous one on drop.
+pub struct ScopedBinder {
+    previous: Viewport,
+}
+
+impl ScopedBinder {
+    pub fn new(viewport: Viewport) -> Self {
+        log::debug!("Setting viewport {:?}", viewport);
+        let previous = Viewport::current();
+        gl_assert_no_err!();
+        unsafe { gl::Viewport(viewport.x, viewport.y, viewport.width, viewport.height); }
+        gl_assert_no_err!();
+        Self { previous }
+    }
+}
+
+impl Drop for ScopedBinder {
+    fn drop(&mut self) {
+        log::debug!("Restoring viewport {:?}", self.previous);
+        let Viewport { x, y, width, height } = self.previous;
+        gl_assert_no_err!();
+        unsafe { gl::Viewport(x, y, width, height); }
+        gl_assert_no_err!();
+    }
+}
+
+/// Run one render pass per viewport, e.g. split-screen views of the same scene from different cameras.
+pub fn render_split(viewports: &[Viewport], mut render: impl FnMut(usize, &Viewport)) {
+    for (index, viewport) in viewports.iter().enumerate() {
+        let _viewport_binder = viewport.scoped_binder();
+        render(index, viewport);
+    }
+}